@@ -0,0 +1,387 @@
+//! Pluggable Section ID algorithms
+//!
+//! Every game version turns a name into a Section ID the same way: each
+//! character adds a value to a running sum, the class may add an offset, and
+//! the guild is `sum % 10`. What differs is the value of each character and
+//! whether the class counts. [`SectionIdAlgorithm`] captures those two
+//! choices so private servers that changed either can be supported.
+//!
+//! [`GameVersion::algorithm`] returns the algorithm the game uses for a
+//! version, and the functions that take a [`GameVersion`] use it. Each of them
+//! has a `_with` form that takes the algorithm instead
+//! ([`calculate_with`](crate::calculate_with),
+//! [`explain_with`](crate::explain_with),
+//! [`find_names_with`](crate::find_names_with) and so on), so a custom
+//! algorithm can be used next to the built-in ones. Functions that cover
+//! several versions, like [`compare_with`](crate::compare_with), take an
+//! [`Algorithms`] with one algorithm per version.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{
+//!     calculate, calculate_with, CharacterClass, GameVersion, PsoidError, SectionIdAlgorithm,
+//! };
+//!
+//! /// A Blue Burst server that ignores the class
+//! struct NoClassOffset;
+//!
+//! impl SectionIdAlgorithm for NoClassOffset {
+//!     fn name(&self) -> &str {
+//!         "no-class-offset"
+//!     }
+//!
+//!     fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError> {
+//!         GameVersion::BlueBurst.algorithm().char_value(index, ch)
+//!     }
+//! }
+//!
+//! let class = Some(CharacterClass::HUmar);
+//! assert_eq!(calculate_with("PSO Player", &NoClassOffset, class).unwrap().name(), "Pinkal");
+//! assert_eq!(calculate("PSO Player", GameVersion::BlueBurst, class).unwrap().name(), "Viridia");
+//! ```
+
+use std::fmt;
+
+use crate::{CharStep, CharacterClass, GameVersion, Guild, PsoidError};
+
+/// A way of turning a character name and class into a Section ID
+///
+/// Implementations decide what each character and class is worth and how
+/// much room each character takes; the sum, the `% 10` and the name length
+/// check are shared by every algorithm.
+pub trait SectionIdAlgorithm {
+    /// A short name for the algorithm, like "ascii" or "blueburst"
+    fn name(&self) -> &str;
+
+    /// Get the value a character adds to the sum
+    ///
+    /// `index` is the character's position in the name, and the value may
+    /// depend on it. Fails if the character can't be used there.
+    fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError>;
+
+    /// Get how much of the [`MAX_NAME_LEN`](crate::MAX_NAME_LEN) limit a
+    /// character uses
+    ///
+    /// Defaults to 1, so the limit counts characters.
+    fn char_len(&self, ch: char) -> usize {
+        let _ = ch;
+        1
+    }
+
+    /// Get the amount the class adds to the sum
+    ///
+    /// Defaults to 0, so the class is ignored.
+    fn class_offset(&self, class: Option<CharacterClass>) -> u32 {
+        let _ = class;
+        0
+    }
+
+    /// Calculate the Section ID for a name and class
+    fn section_id(&self, name: &str, class: Option<CharacterClass>) -> Result<Guild, PsoidError> {
+        let (_, _, sum) = trace(self, name, class)?;
+        Ok(Guild::from_id(sum % 10))
+    }
+}
+
+/// Add up a name's character values, then the class offset
+///
+/// Returns every character's step, the class offset and the total. This is
+/// the only place a sum is built, for [`SectionIdAlgorithm::section_id`] and
+/// [`explain`](crate::explain) alike; a total that doesn't fit in a `u32`
+/// fails with [`PsoidError::SumOverflow`].
+pub(crate) fn trace<A: SectionIdAlgorithm + ?Sized>(
+    algorithm: &A,
+    name: &str,
+    class: Option<CharacterClass>,
+) -> Result<(Vec<CharStep>, u32, u32), PsoidError> {
+    crate::check_name(algorithm, name)?;
+
+    let mut sum: u32 = 0;
+    let mut steps = Vec::with_capacity(name.len());
+    for (index, ch) in name.chars().enumerate() {
+        let value = algorithm.char_value(index, ch)?;
+        sum = sum.checked_add(value).ok_or(PsoidError::SumOverflow)?;
+        steps.push(CharStep {
+            index,
+            ch,
+            value,
+            running_sum: sum,
+        });
+    }
+
+    let class_offset = algorithm.class_offset(class);
+    let sum = sum
+        .checked_add(class_offset)
+        .ok_or(PsoidError::SumOverflow)?;
+    Ok((steps, class_offset, sum))
+}
+
+/// The V1, V2 and GameCube algorithm: each character is worth its ASCII byte
+/// and the class is ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AsciiAlgorithm;
+
+impl SectionIdAlgorithm for AsciiAlgorithm {
+    fn name(&self) -> &str {
+        "ascii"
+    }
+
+    fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError> {
+        if !ch.is_ascii() {
+            return Err(PsoidError::NonAscii { index, ch });
+        }
+        Ok(ch as u32)
+    }
+}
+
+/// The Blue Burst algorithm: each character is worth a value from the game's
+/// own table and the class adds [`CharacterClass::blueburst_offset`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlueBurstAlgorithm;
+
+impl SectionIdAlgorithm for BlueBurstAlgorithm {
+    fn name(&self) -> &str {
+        "blueburst"
+    }
+
+    fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError> {
+        crate::get_blueburst_char_value(index, ch)
+    }
+
+    fn class_offset(&self, class: Option<CharacterClass>) -> u32 {
+        class.map_or(0, |class| class.blueburst_offset())
+    }
+}
+
+/// The Japanese V1, V2 and GameCube algorithm: each character is worth the
+/// sum of its Shift-JIS bytes and the class is ignored
+///
+/// ASCII is stored unchanged, so ASCII names calculate the same as with
+/// [`AsciiAlgorithm`]. Bytes are summed as unsigned values. The name limit
+/// counts bytes too, so it fits 12 ASCII or half-width katakana characters but
+/// only 6 full-width ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShiftJisAlgorithm;
+
+impl SectionIdAlgorithm for ShiftJisAlgorithm {
+    fn name(&self) -> &str {
+        "shift-jis"
+    }
+
+    fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError> {
+        let code = shift_jis(ch).ok_or(PsoidError::UnsupportedCharacter { index, ch })?;
+        Ok((code >> 8) + (code & 0xFF))
+    }
+
+    fn char_len(&self, ch: char) -> usize {
+        match shift_jis(ch) {
+            Some(0x00..=0xFF) => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Encode a character as Shift-JIS, with two-byte characters as `lead << 8 | trail`
+///
+/// Only covers what kana names are typed with: ASCII, half-width katakana,
+/// hiragana, katakana, the ideographic space and punctuation around kana, and
+/// full-width digits and letters.
+fn shift_jis(ch: char) -> Option<u32> {
+    let code = ch as u32;
+    let offset = |first: u32, encoded: u32| encoded + code - first;
+    let encoded = match code {
+        0x00..=0x7F => code,
+        0xFF61..=0xFF9F => offset(0xFF61, 0xA1),
+        0x3000 => 0x8140,
+        0x3001 => 0x8141,
+        0x3002 => 0x8142,
+        0x30FB => 0x8145,
+        0x309B => 0x814A,
+        0x309C => 0x814B,
+        0x30FC => 0x815B,
+        0xFF10..=0xFF19 => offset(0xFF10, 0x824F),
+        0xFF21..=0xFF3A => offset(0xFF21, 0x8260),
+        0xFF41..=0xFF5A => offset(0xFF41, 0x8281),
+        0x3041..=0x3093 => offset(0x3041, 0x829F),
+        // Shift-JIS skips 0x7F as a trail byte, so katakana are split in two
+        0x30A1..=0x30DF => offset(0x30A1, 0x8340),
+        0x30E0..=0x30F6 => offset(0x30E0, 0x8380),
+        _ => return None,
+    };
+    Some(encoded)
+}
+
+impl GameVersion {
+    /// Get the algorithm the game itself uses for this version
+    pub fn algorithm(&self) -> &'static dyn SectionIdAlgorithm {
+        match self {
+            GameVersion::V1 | GameVersion::V2 | GameVersion::GameCube => &AsciiAlgorithm,
+            GameVersion::BlueBurst => &BlueBurstAlgorithm,
+        }
+    }
+}
+
+/// One algorithm for each game version
+///
+/// Starts from the game's own algorithms; replace any of them with
+/// [`Algorithms::with`].
+#[derive(Clone, Copy)]
+pub struct Algorithms<'a> {
+    algorithms: [&'a dyn SectionIdAlgorithm; 4],
+}
+
+impl<'a> Algorithms<'a> {
+    /// The algorithms the game itself uses
+    pub fn builtin() -> Algorithms<'static> {
+        Algorithms {
+            algorithms: GameVersion::ALL.map(|version| version.algorithm()),
+        }
+    }
+
+    /// Use `algorithm` for `version`
+    pub fn with(mut self, version: GameVersion, algorithm: &'a dyn SectionIdAlgorithm) -> Self {
+        self.algorithms[Self::position(version)] = algorithm;
+        self
+    }
+
+    /// Get the algorithm for a version
+    pub fn get(&self, version: GameVersion) -> &'a dyn SectionIdAlgorithm {
+        self.algorithms[Self::position(version)]
+    }
+
+    fn position(version: GameVersion) -> usize {
+        GameVersion::ALL
+            .iter()
+            .position(|&other| other == version)
+            .expect("ALL has every version")
+    }
+}
+
+impl Default for Algorithms<'static> {
+    fn default() -> Self {
+        Algorithms::builtin()
+    }
+}
+
+/// Show each version's algorithm name
+impl fmt::Debug for Algorithms<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                GameVersion::ALL
+                    .iter()
+                    .map(|&version| (version, self.get(version).name())),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts every character as 1, so the Section ID is the name's length
+    struct Length;
+
+    impl SectionIdAlgorithm for Length {
+        fn name(&self) -> &str {
+            "length"
+        }
+
+        fn char_value(&self, _index: usize, _ch: char) -> Result<u32, PsoidError> {
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_builtin_algorithms_match_calculate() {
+        for version in GameVersion::ALL {
+            for class in [None, Some(CharacterClass::HUcast)] {
+                for name in ["Bob", "PSO Player", "Testing"] {
+                    assert_eq!(
+                        version.algorithm().section_id(name, class).unwrap(),
+                        crate::calculate(name, version, class).unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_builtin_names() {
+        assert_eq!(GameVersion::V2.algorithm().name(), "ascii");
+        assert_eq!(GameVersion::BlueBurst.algorithm().name(), "blueburst");
+    }
+
+    #[test]
+    fn test_builtin_class_offsets() {
+        let class = Some(CharacterClass::RAmar);
+        assert_eq!(AsciiAlgorithm.class_offset(class), 0);
+        assert_eq!(BlueBurstAlgorithm.class_offset(class), 8);
+        assert_eq!(BlueBurstAlgorithm.class_offset(None), 0);
+    }
+
+    #[test]
+    fn test_custom_algorithm() {
+        assert_eq!(Length.section_id("Bob", None).unwrap(), Guild::from_id(3));
+        assert_eq!(
+            Length.section_id("ABCDEFGHIJKL", None).unwrap(),
+            Guild::from_id(2)
+        );
+        assert_eq!(Length.section_id("", None), Err(PsoidError::EmptyName));
+        assert_eq!(
+            Length.section_id("ABCDEFGHIJKLM", None),
+            Err(PsoidError::NameTooLong { len: 13, max: 12 })
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        struct Huge;
+
+        impl SectionIdAlgorithm for Huge {
+            fn name(&self) -> &str {
+                "huge"
+            }
+
+            fn char_value(&self, _index: usize, _ch: char) -> Result<u32, PsoidError> {
+                Ok(u32::MAX / 2)
+            }
+        }
+
+        assert_eq!(Huge.section_id("AB", None), Ok(Guild::from_id(4)));
+        assert_eq!(Huge.section_id("ABC", None), Err(PsoidError::SumOverflow));
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let algorithms = Algorithms::builtin().with(GameVersion::V2, &Length);
+        assert_eq!(algorithms.get(GameVersion::V1).name(), "ascii");
+        assert_eq!(algorithms.get(GameVersion::V2).name(), "length");
+        assert_eq!(algorithms.get(GameVersion::BlueBurst).name(), "blueburst");
+        assert_eq!(
+            format!("{:?}", Algorithms::default()),
+            "{V1: \"ascii\", V2: \"ascii\", GameCube: \"ascii\", BlueBurst: \"blueburst\"}"
+        );
+    }
+
+    #[test]
+    fn test_shift_jis_values() {
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'B'), Ok(66));
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'ｱ'), Ok(0xB1));
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'あ'), Ok(0x82 + 0xA0));
+        // Either side of the gap at trail byte 0x7F
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'ミ'), Ok(0x83 + 0x7E));
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'ム'), Ok(0x83 + 0x80));
+        assert_eq!(ShiftJisAlgorithm.char_value(0, 'Ｚ'), Ok(0x82 + 0x79));
+    }
+
+    #[test]
+    fn test_builtin_errors() {
+        assert_eq!(
+            AsciiAlgorithm.section_id("Bé", None),
+            Err(PsoidError::NonAscii { index: 1, ch: 'é' })
+        );
+    }
+}
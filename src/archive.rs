@@ -0,0 +1,311 @@
+//! The AFS and GSL archives the game packs its drop tables into
+//!
+//! AFS starts with `AFS\0`, a file count and an `(offset, size)` pair per
+//! file, all little-endian; files are only known by their position. GSL is a
+//! list of 0x30-byte headers (a NUL-padded 0x20-byte name, an offset in
+//! 0x800-byte blocks, a size and 8 unused bytes) ending at an empty name.
+//! GameCube GSLs are big-endian and Blue Burst GSLs little-endian.
+//!
+//! Both writers place every file on a 0x800-byte boundary and pad with zeros.
+
+use crate::{Difficulty, Episode, Guild, PsoidError};
+
+const BLOCK: usize = 0x800;
+const AFS_MAGIC: &[u8; 4] = b"AFS\0";
+const GSL_ENTRY: usize = 0x30;
+const GSL_NAME: usize = 0x20;
+
+/// Files in a GSL archive with their names
+type NamedFiles = Vec<(String, Vec<u8>)>;
+
+/// Read every file in an AFS archive, in order
+fn read_afs(bytes: &[u8]) -> Result<Vec<Vec<u8>>, PsoidError> {
+    if bytes.get(..4) != Some(AFS_MAGIC) {
+        return Err(malformed("not an AFS archive"));
+    }
+    let count = read_u32(bytes, 4, false)? as usize;
+
+    (0..count)
+        .map(|index| {
+            let offset = read_u32(bytes, 8 + index * 8, false)? as usize;
+            let size = read_u32(bytes, 12 + index * 8, false)? as usize;
+            slice(bytes, offset, size)
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| malformed(format!("AFS file {} is out of bounds", index)))
+        })
+        .collect()
+}
+
+/// Pack files into an AFS archive
+fn write_afs(files: &[&[u8]]) -> Vec<u8> {
+    let mut header = AFS_MAGIC.to_vec();
+    header.extend((files.len() as u32).to_le_bytes());
+    let mut offset = align(8 + files.len() * 8);
+    for file in files {
+        header.extend((offset as u32).to_le_bytes());
+        header.extend((file.len() as u32).to_le_bytes());
+        offset += align(file.len());
+    }
+    pack(header, files)
+}
+
+/// Read every named file in a GSL archive, and whether it was big-endian
+fn read_gsl(bytes: &[u8]) -> Result<(NamedFiles, bool), PsoidError> {
+    let big_endian = gsl_is_big_endian(bytes);
+    let mut files = Vec::new();
+
+    for start in (0..).map(|index| index * GSL_ENTRY) {
+        let entry = bytes
+            .get(start..start + GSL_ENTRY)
+            .ok_or_else(|| malformed("GSL header has no end marker"))?;
+        let name = &entry[..GSL_NAME];
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(GSL_NAME)];
+        if name.is_empty() {
+            break;
+        }
+        let name =
+            String::from_utf8(name.to_vec()).map_err(|_| malformed("GSL file name is not text"))?;
+
+        let offset = read_u32(entry, GSL_NAME, big_endian)? as usize * BLOCK;
+        let size = read_u32(entry, GSL_NAME + 4, big_endian)? as usize;
+        let data = slice(bytes, offset, size)
+            .ok_or_else(|| malformed(format!("GSL file {} is out of bounds", name)))?;
+        files.push((name, data.to_vec()));
+    }
+
+    Ok((files, big_endian))
+}
+
+/// Pack named files into a GSL archive
+fn write_gsl(files: &[(&str, &[u8])], big_endian: bool) -> Result<Vec<u8>, PsoidError> {
+    let mut header = Vec::new();
+    let mut offset = align((files.len() + 1) * GSL_ENTRY);
+    for (name, data) in files {
+        if name.is_empty() || name.len() >= GSL_NAME {
+            return Err(malformed(format!(
+                "GSL file name {:?} is not 1-31 bytes",
+                name
+            )));
+        }
+        let mut entry = [0u8; GSL_ENTRY];
+        entry[..name.len()].copy_from_slice(name.as_bytes());
+        entry[GSL_NAME..GSL_NAME + 4].copy_from_slice(&u32_bytes(offset / BLOCK, big_endian));
+        entry[GSL_NAME + 4..GSL_NAME + 8].copy_from_slice(&u32_bytes(data.len(), big_endian));
+        header.extend(entry);
+        offset += align(data.len());
+    }
+    let data: Vec<&[u8]> = files.iter().map(|(_, data)| *data).collect();
+    Ok(pack(header, &data))
+}
+
+/// Name a per-Section-ID table the way the game's GSL archives do, e.g.
+/// `ItemPTn0.rel` (Episode I), `ItemPTlh3.rel` (II) or `ItemPTsu9.rel` (IV)
+fn table_name(prefix: &str, episode: Episode, difficulty: Difficulty, guild: Guild) -> String {
+    let episode = match episode {
+        Episode::I => "",
+        Episode::II => "l",
+        Episode::IV => "s",
+    };
+    let difficulty = match difficulty {
+        Difficulty::Normal => 'n',
+        Difficulty::Hard => 'h',
+        Difficulty::VeryHard => 'v',
+        Difficulty::Ultimate => 'u',
+    };
+    format!("{}{}{}{}.rel", prefix, episode, difficulty, guild.id())
+}
+
+/// Find the episode, difficulty and guild a [`table_name`] stands for
+fn parse_table_name(prefix: &str, name: &str) -> Result<(Episode, Difficulty, Guild), PsoidError> {
+    Episode::ALL
+        .into_iter()
+        .flat_map(|episode| Difficulty::ALL.map(|difficulty| (episode, difficulty)))
+        .flat_map(|(episode, difficulty)| Guild::ALL.map(|guild| (episode, difficulty, guild)))
+        .find(|&(episode, difficulty, guild)| {
+            table_name(prefix, episode, difficulty, guild).eq_ignore_ascii_case(name)
+        })
+        .ok_or_else(|| malformed(format!("unexpected file {} in archive", name)))
+}
+
+/// Get the AFS position of an Episode I table: ten guilds per difficulty
+fn afs_index(difficulty: Difficulty, guild: Guild) -> usize {
+    difficulty as usize * Guild::ALL.len() + guild.id() as usize
+}
+
+/// The episode, difficulty and Section ID a table in an archive is for
+pub(crate) type TableKey = (Episode, Difficulty, Guild);
+
+/// Raw per-Section-ID tables in archive order
+pub(crate) type Tables = Vec<(TableKey, Vec<u8>)>;
+
+/// Read the Episode I tables in a V2 AFS archive, ordered by difficulty then
+/// Section ID
+pub(crate) fn read_afs_tables(bytes: &[u8]) -> Result<Tables, PsoidError> {
+    let files = read_afs(bytes)?;
+    let keys: Vec<TableKey> = Difficulty::ALL
+        .into_iter()
+        .flat_map(|difficulty| Guild::ALL.map(|guild| (Episode::I, difficulty, guild)))
+        .collect();
+    if files.len() > keys.len() {
+        return Err(malformed(format!(
+            "AFS archive has {} tables, expected at most {}",
+            files.len(),
+            keys.len()
+        )));
+    }
+    Ok(keys.into_iter().zip(files).collect())
+}
+
+/// Read the tables in a GSL archive whose names start with `prefix`, and
+/// whether it was big-endian
+pub(crate) fn read_gsl_tables(prefix: &str, bytes: &[u8]) -> Result<(Tables, bool), PsoidError> {
+    let (files, big_endian) = read_gsl(bytes)?;
+    let tables = files
+        .into_iter()
+        .map(|(name, data)| Ok((parse_table_name(prefix, &name)?, data)))
+        .collect::<Result<_, PsoidError>>()?;
+    Ok((tables, big_endian))
+}
+
+/// Pack Episode I tables into a V2 AFS archive
+///
+/// AFS files are found by position, so the tables must be in order from
+/// Normal Viridia with none missing.
+pub(crate) fn write_afs_tables(tables: &[(TableKey, &[u8])]) -> Result<Vec<u8>, PsoidError> {
+    for (index, &((episode, difficulty, guild), _)) in tables.iter().enumerate() {
+        if episode != Episode::I || afs_index(difficulty, guild) != index {
+            return Err(malformed(format!(
+                "{} {} {} can't be table {} of an AFS archive",
+                episode,
+                difficulty,
+                guild.name(),
+                index
+            )));
+        }
+    }
+    let files: Vec<&[u8]> = tables.iter().map(|(_, data)| *data).collect();
+    Ok(write_afs(&files))
+}
+
+/// Pack tables into a GSL archive, naming them with `prefix`
+pub(crate) fn write_gsl_tables(
+    prefix: &str,
+    tables: &[(TableKey, &[u8])],
+    big_endian: bool,
+) -> Result<Vec<u8>, PsoidError> {
+    let names: Vec<String> = tables
+        .iter()
+        .map(|&((episode, difficulty, guild), _)| table_name(prefix, episode, difficulty, guild))
+        .collect();
+    let files: Vec<(&str, &[u8])> = names
+        .iter()
+        .zip(tables)
+        .map(|(name, (_, data))| (name.as_str(), *data))
+        .collect();
+    write_gsl(&files, big_endian)
+}
+
+pub(crate) fn malformed(message: impl Into<String>) -> PsoidError {
+    PsoidError::MalformedFile(message.into())
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize, big_endian: bool) -> Result<u32, PsoidError> {
+    let word: [u8; 4] = bytes
+        .get(offset..offset + 4)
+        .and_then(|word| word.try_into().ok())
+        .ok_or_else(|| malformed("file is truncated"))?;
+    Ok(if big_endian {
+        u32::from_be_bytes(word)
+    } else {
+        u32::from_le_bytes(word)
+    })
+}
+
+fn u32_bytes(value: usize, big_endian: bool) -> [u8; 4] {
+    if big_endian {
+        (value as u32).to_be_bytes()
+    } else {
+        (value as u32).to_le_bytes()
+    }
+}
+
+/// A GSL is big-endian if its first file only fits when read that way
+fn gsl_is_big_endian(bytes: &[u8]) -> bool {
+    let fits = |big_endian| {
+        let offset = read_u32(bytes, GSL_NAME, big_endian).map(|offset| offset as usize);
+        let size = read_u32(bytes, GSL_NAME + 4, big_endian).map(|size| size as usize);
+        matches!((offset, size), (Ok(offset), Ok(size))
+            if offset.checked_mul(BLOCK).and_then(|start| slice(bytes, start, size)).is_some())
+    };
+    !fits(false) && fits(true)
+}
+
+fn slice(bytes: &[u8], offset: usize, size: usize) -> Option<&[u8]> {
+    bytes.get(offset..offset.checked_add(size)?)
+}
+
+fn align(len: usize) -> usize {
+    len.div_ceil(BLOCK) * BLOCK
+}
+
+/// Pad the header and each file to a block and join them
+fn pack(mut out: Vec<u8>, files: &[&[u8]]) -> Vec<u8> {
+    out.resize(align(out.len()), 0);
+    for file in files {
+        out.extend_from_slice(file);
+        out.resize(align(out.len()), 0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_afs_round_trip() {
+        let files: [&[u8]; 2] = [b"first", &[7; 0x900]];
+        let bytes = write_afs(&files);
+        assert_eq!(bytes.len(), 0x800 * 4);
+        assert_eq!(&bytes[..8], b"AFS\0\x02\0\0\0");
+        assert_eq!(read_afs(&bytes).unwrap(), files.map(<[u8]>::to_vec));
+
+        assert_eq!(
+            read_afs(b"AFX\0").unwrap_err(),
+            PsoidError::MalformedFile("not an AFS archive".to_string())
+        );
+        assert_eq!(
+            read_afs(&bytes[..0x1000]).unwrap_err(),
+            PsoidError::MalformedFile("AFS file 1 is out of bounds".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gsl_round_trip() {
+        let files: [(&str, &[u8]); 2] = [("a.rel", b"first"), ("b.rel", &[7; 0x30])];
+        for big_endian in [false, true] {
+            let bytes = write_gsl(&files, big_endian).unwrap();
+            let (read, detected) = read_gsl(&bytes).unwrap();
+            assert_eq!(detected, big_endian);
+            assert_eq!(read[0], ("a.rel".to_string(), b"first".to_vec()));
+            assert_eq!(read[1].1, vec![7; 0x30]);
+        }
+        assert!(write_gsl(&[("a-very-long-name-for-a-gsl-file.rel", b"")], false).is_err());
+    }
+
+    #[test]
+    fn test_table_names() {
+        let name = table_name("ItemPT", Episode::II, Difficulty::Hard, Guild::Redria);
+        assert_eq!(name, "ItemPTlh6.rel");
+        assert_eq!(
+            parse_table_name("ItemPT", "itemptlh6.rel").unwrap(),
+            (Episode::II, Difficulty::Hard, Guild::Redria)
+        );
+        assert_eq!(
+            table_name("ItemRT", Episode::I, Difficulty::Normal, Guild::Viridia),
+            "ItemRTn0.rel"
+        );
+        assert!(parse_table_name("ItemPT", "ItemRTn0.rel").is_err());
+        assert_eq!(afs_index(Difficulty::Ultimate, Guild::Whitill), 39);
+    }
+}
@@ -0,0 +1,226 @@
+//! Game areas
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Area, Episode};
+//!
+//! let area: Area = "caves 2".parse().unwrap();
+//! assert_eq!(area, Area::Caves2);
+//! assert_eq!(area.episode(), Episode::I);
+//! assert_eq!(Area::in_episode(Episode::IV).len(), 8);
+//! ```
+
+use crate::{normalize, Episode, PsoidError};
+use std::fmt;
+use std::str::FromStr;
+
+/// An area with item boxes, in the order they're played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Area {
+    Forest1,
+    Forest2,
+    Caves1,
+    Caves2,
+    Caves3,
+    Mines1,
+    Mines2,
+    Ruins1,
+    Ruins2,
+    Ruins3,
+    TempleAlpha,
+    TempleBeta,
+    SpaceshipAlpha,
+    SpaceshipBeta,
+    JungleNorth,
+    JungleEast,
+    Mountain,
+    Seaside,
+    SeabedUpper,
+    SeabedLower,
+    ControlTower,
+    CraterEast,
+    CraterWest,
+    CraterSouth,
+    CraterNorth,
+    CraterInterior,
+    Desert1,
+    Desert2,
+    Desert3,
+}
+
+impl Area {
+    /// Every area, Episode I first
+    pub const ALL: [Area; 29] = [
+        Area::Forest1,
+        Area::Forest2,
+        Area::Caves1,
+        Area::Caves2,
+        Area::Caves3,
+        Area::Mines1,
+        Area::Mines2,
+        Area::Ruins1,
+        Area::Ruins2,
+        Area::Ruins3,
+        Area::TempleAlpha,
+        Area::TempleBeta,
+        Area::SpaceshipAlpha,
+        Area::SpaceshipBeta,
+        Area::JungleNorth,
+        Area::JungleEast,
+        Area::Mountain,
+        Area::Seaside,
+        Area::SeabedUpper,
+        Area::SeabedLower,
+        Area::ControlTower,
+        Area::CraterEast,
+        Area::CraterWest,
+        Area::CraterSouth,
+        Area::CraterNorth,
+        Area::CraterInterior,
+        Area::Desert1,
+        Area::Desert2,
+        Area::Desert3,
+    ];
+
+    /// Get the name of the area as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Area::Forest1 => "Forest 1",
+            Area::Forest2 => "Forest 2",
+            Area::Caves1 => "Caves 1",
+            Area::Caves2 => "Caves 2",
+            Area::Caves3 => "Caves 3",
+            Area::Mines1 => "Mines 1",
+            Area::Mines2 => "Mines 2",
+            Area::Ruins1 => "Ruins 1",
+            Area::Ruins2 => "Ruins 2",
+            Area::Ruins3 => "Ruins 3",
+            Area::TempleAlpha => "VR Temple Alpha",
+            Area::TempleBeta => "VR Temple Beta",
+            Area::SpaceshipAlpha => "VR Spaceship Alpha",
+            Area::SpaceshipBeta => "VR Spaceship Beta",
+            Area::JungleNorth => "Jungle Area North",
+            Area::JungleEast => "Jungle Area East",
+            Area::Mountain => "Mountain Area",
+            Area::Seaside => "Seaside Area",
+            Area::SeabedUpper => "Seabed Upper Levels",
+            Area::SeabedLower => "Seabed Lower Levels",
+            Area::ControlTower => "Control Tower",
+            Area::CraterEast => "Crater East",
+            Area::CraterWest => "Crater West",
+            Area::CraterSouth => "Crater South",
+            Area::CraterNorth => "Crater North",
+            Area::CraterInterior => "Crater Interior",
+            Area::Desert1 => "Subterranean Desert 1",
+            Area::Desert2 => "Subterranean Desert 2",
+            Area::Desert3 => "Subterranean Desert 3",
+        }
+    }
+
+    /// Get the episode the area belongs to
+    pub fn episode(&self) -> Episode {
+        match self {
+            Area::Forest1
+            | Area::Forest2
+            | Area::Caves1
+            | Area::Caves2
+            | Area::Caves3
+            | Area::Mines1
+            | Area::Mines2
+            | Area::Ruins1
+            | Area::Ruins2
+            | Area::Ruins3 => Episode::I,
+            Area::TempleAlpha
+            | Area::TempleBeta
+            | Area::SpaceshipAlpha
+            | Area::SpaceshipBeta
+            | Area::JungleNorth
+            | Area::JungleEast
+            | Area::Mountain
+            | Area::Seaside
+            | Area::SeabedUpper
+            | Area::SeabedLower
+            | Area::ControlTower => Episode::II,
+            Area::CraterEast
+            | Area::CraterWest
+            | Area::CraterSouth
+            | Area::CraterNorth
+            | Area::CraterInterior
+            | Area::Desert1
+            | Area::Desert2
+            | Area::Desert3 => Episode::IV,
+        }
+    }
+
+    /// Get every area in an episode, in play order
+    pub fn in_episode(episode: Episode) -> Vec<Area> {
+        Area::ALL
+            .into_iter()
+            .filter(|area| area.episode() == episode)
+            .collect()
+    }
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse an area name, ignoring case, spaces, dashes and underscores
+///
+/// The "VR", "Area" and "Levels" parts of the full names are optional, and
+/// "Subterranean Desert" can be shortened to "Desert" ("temple alpha",
+/// "jungle north", "desert 2").
+impl FromStr for Area {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let wanted = short_name(value);
+        Area::ALL
+            .into_iter()
+            .find(|area| short_name(area.name()) == wanted)
+            .ok_or_else(|| PsoidError::UnknownArea(value.to_string()))
+    }
+}
+
+/// Normalize an area name and drop the optional words
+fn short_name(name: &str) -> String {
+    let name = normalize(name);
+    let name = name.strip_prefix("vr").unwrap_or(&name);
+    let name = name.strip_prefix("subterranean").unwrap_or(name);
+    name.replace("area", "").replace("levels", "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_area() {
+        for area in Area::ALL {
+            assert_eq!(area.name().parse::<Area>().unwrap(), area);
+            assert_eq!(area.name().to_uppercase().parse::<Area>().unwrap(), area);
+        }
+        assert_eq!("forest-1".parse::<Area>().unwrap(), Area::Forest1);
+        assert_eq!("temple alpha".parse::<Area>().unwrap(), Area::TempleAlpha);
+        assert_eq!("Jungle North".parse::<Area>().unwrap(), Area::JungleNorth);
+        assert_eq!("seabed lower".parse::<Area>().unwrap(), Area::SeabedLower);
+        assert_eq!("desert 3".parse::<Area>().unwrap(), Area::Desert3);
+        assert_eq!(
+            "Forest 3".parse::<Area>().unwrap_err(),
+            PsoidError::UnknownArea("Forest 3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_episodes() {
+        assert_eq!(Area::in_episode(Episode::I).len(), 10);
+        assert_eq!(Area::in_episode(Episode::II).len(), 11);
+        assert_eq!(Area::in_episode(Episode::IV).len(), 8);
+        assert_eq!(Area::Ruins3.episode(), Episode::I);
+        assert_eq!(Area::ControlTower.episode(), Episode::II);
+        assert_eq!(Area::CraterInterior.episode(), Episode::IV);
+    }
+}
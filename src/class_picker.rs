@@ -0,0 +1,136 @@
+//! Blue Burst class picker
+//!
+//! In Blue Burst the class offset is added to the name sum, so a name can
+//! reach different guilds depending on the class it's used with.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{classes_for_guild, guilds_with_append, CharacterClass, Guild};
+//!
+//! let classes = classes_for_guild("Hunter", Guild::Redria).unwrap();
+//! assert_eq!(classes, vec![CharacterClass::HUnewearl, CharacterClass::RAmarl]);
+//!
+//! let options = guilds_with_append("Hunter", CharacterClass::HUmar).unwrap();
+//! assert_eq!(options.len(), 10);
+//! ```
+
+use crate::{
+    calculate, supported_chars, CharacterClass, GameVersion, Guild, PsoidError, MAX_NAME_LEN,
+};
+
+/// The characters that move a name to a guild when appended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendOption {
+    pub guild: Guild,
+    /// Every character that reaches `guild`, in ASCII order
+    pub chars: Vec<char>,
+}
+
+/// Find every Blue Burst class that gives `name` the `target` guild
+///
+/// Classes are returned in [`CharacterClass::ALL`] order.
+pub fn classes_for_guild(name: &str, target: Guild) -> Result<Vec<CharacterClass>, PsoidError> {
+    let mut classes = Vec::new();
+    for class in CharacterClass::ALL {
+        if calculate(name, GameVersion::BlueBurst, Some(class))? == target {
+            classes.push(class);
+        }
+    }
+    Ok(classes)
+}
+
+/// Find every guild `name` can reach in Blue Burst by appending one character
+///
+/// Guilds are returned in ID order; guilds no character reaches are left out.
+/// A name that is already at the maximum length can't be extended, so the
+/// result is empty.
+pub fn guilds_with_append(
+    name: &str,
+    class: CharacterClass,
+) -> Result<Vec<AppendOption>, PsoidError> {
+    let current = calculate(name, GameVersion::BlueBurst, Some(class))?;
+    let len = name.chars().count();
+    if len >= MAX_NAME_LEN {
+        return Ok(Vec::new());
+    }
+
+    let mut options: Vec<AppendOption> = Guild::ALL
+        .into_iter()
+        .map(|guild| AppendOption {
+            guild,
+            chars: Vec::new(),
+        })
+        .collect();
+
+    let algorithm = GameVersion::BlueBurst.algorithm();
+    for ch in supported_chars(algorithm) {
+        // Trailing spaces aren't visible in-game, so don't suggest them
+        if ch == ' ' {
+            continue;
+        }
+        let value = algorithm.char_value(len, ch)?;
+        let id = (current.id() + value) % 10;
+        options[id as usize].chars.push(ch);
+    }
+
+    options.retain(|option| !option.chars.is_empty());
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classes_for_guild() {
+        for guild in Guild::ALL {
+            let classes = classes_for_guild("PSO Player", guild).unwrap();
+            for class in CharacterClass::ALL {
+                let reaches =
+                    calculate("PSO Player", GameVersion::BlueBurst, Some(class)).unwrap() == guild;
+                assert_eq!(classes.contains(&class), reaches);
+            }
+        }
+
+        assert_eq!(
+            classes_for_guild("PSO Player", Guild::Yellowboze).unwrap(),
+            vec![CharacterClass::FOnewearl]
+        );
+        assert_eq!(
+            classes_for_guild("PSO Player", Guild::Greennill).unwrap(),
+            vec![CharacterClass::HUnewearl, CharacterClass::RAmarl]
+        );
+    }
+
+    #[test]
+    fn test_guilds_with_append() {
+        let options = guilds_with_append("Tank", CharacterClass::HUcast).unwrap();
+        assert_eq!(options.len(), 10);
+
+        for option in &options {
+            for ch in &option.chars {
+                let name = format!("Tank{}", ch);
+                let guild =
+                    calculate(&name, GameVersion::BlueBurst, Some(CharacterClass::HUcast)).unwrap();
+                assert_eq!(guild, option.guild);
+            }
+        }
+
+        // Tank/HUcast is Pinkal (5); 'F' is worth 0 and 'A' is worth 5
+        assert!(options[5].chars.contains(&'F'));
+        assert!(options[0].chars.contains(&'A'));
+        assert!(!options.iter().any(|option| option.chars.contains(&' ')));
+    }
+
+    #[test]
+    fn test_guilds_with_append_full_name() {
+        let options = guilds_with_append("ABCDEFGHIJKL", CharacterClass::FOmar).unwrap();
+        assert!(options.is_empty());
+
+        assert_eq!(
+            guilds_with_append("", CharacterClass::FOmar).unwrap_err(),
+            PsoidError::EmptyName
+        );
+    }
+}
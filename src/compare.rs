@@ -0,0 +1,264 @@
+//! Compare one name across every version and Blue Burst class
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{compare, CharacterClass, Guild};
+//!
+//! let comparison = compare("PSO Player").unwrap();
+//! assert_eq!(comparison.baseline, Some(Guild::Pinkal));
+//!
+//! let ramar = comparison
+//!     .entries
+//!     .iter()
+//!     .find(|entry| entry.class == Some(CharacterClass::RAmar))
+//!     .unwrap();
+//! assert_eq!(ramar.result, Ok(Guild::Bluefull));
+//! assert!(ramar.differs);
+//! ```
+
+use crate::{
+    calculate_with, Algorithms, CharacterClass, Difficulty, DropTableSet, GameVersion, Guild,
+    GuildReport, PsoidError,
+};
+use std::fmt;
+
+/// The result of one version/class combination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComparisonEntry {
+    pub version: GameVersion,
+    /// The Blue Burst class, or `None` for V1, V2, GameCube and classless
+    /// Blue Burst
+    pub class: Option<CharacterClass>,
+    pub result: Result<Guild, PsoidError>,
+    /// Whether the guild differs from the V1, V2 and GameCube byte-sum guild
+    pub differs: bool,
+    /// The guild's drops in this version, or `None` if the calculation failed
+    /// or the difficulty isn't in this version
+    pub report: Option<GuildReport>,
+}
+
+/// A name's Section ID in V1, V2, GameCube and Blue Burst with and without
+/// each class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub name: String,
+    /// The V1, V2 and GameCube byte-sum guild, if the name is valid there
+    pub baseline: Option<Guild>,
+    /// V1, V2, GameCube, Blue Burst without a class, then Blue Burst with each
+    /// class
+    pub entries: Vec<ComparisonEntry>,
+}
+
+/// Calculate a name's Section ID in every version and Blue Burst class
+///
+/// Entries whose calculation fails (for example a name using characters only
+/// one version accepts) keep their error instead of failing the whole
+/// comparison. An error is only returned when no combination succeeds. Drops
+/// are reported from the built-in tables on Normal; use
+/// [`DropTableSet::compare`] for others.
+pub fn compare(name: &str) -> Result<Comparison, PsoidError> {
+    compare_with(name, &Algorithms::builtin())
+}
+
+/// Like [`compare`], calculating each version with its algorithm from
+/// `algorithms`
+///
+/// # Examples
+///
+/// ```
+/// use psoid::{compare_with, Guild, Region};
+///
+/// let comparison = compare_with("リコ", &Region::Japan.algorithms()).unwrap();
+/// assert_eq!(comparison.baseline, Some(Guild::Skyly));
+/// ```
+pub fn compare_with(name: &str, algorithms: &Algorithms) -> Result<Comparison, PsoidError> {
+    DropTableSet::default().compare_with(name, Difficulty::Normal, algorithms)
+}
+
+impl DropTableSet {
+    /// Like [`compare`], reporting each entry's drops from this set's
+    /// tables for its version and `difficulty`
+    pub fn compare(&self, name: &str, difficulty: Difficulty) -> Result<Comparison, PsoidError> {
+        self.compare_with(name, difficulty, &Algorithms::builtin())
+    }
+
+    /// Like [`DropTableSet::compare`], calculating each version with its
+    /// algorithm from `algorithms`
+    pub fn compare_with(
+        &self,
+        name: &str,
+        difficulty: Difficulty,
+        algorithms: &Algorithms,
+    ) -> Result<Comparison, PsoidError> {
+        let mut combinations = vec![
+            (GameVersion::V1, None),
+            (GameVersion::V2, None),
+            (GameVersion::GameCube, None),
+            (GameVersion::BlueBurst, None),
+        ];
+        combinations.extend(
+            CharacterClass::ALL
+                .into_iter()
+                .map(|class| (GameVersion::BlueBurst, Some(class))),
+        );
+
+        let baseline = calculate_with(name, algorithms.get(GameVersion::V1), None).ok();
+        let entries: Vec<ComparisonEntry> = combinations
+            .into_iter()
+            .map(|(version, class)| {
+                let result = calculate_with(name, algorithms.get(version), class);
+                let differs =
+                    matches!((&result, baseline), (Ok(guild), Some(base)) if *guild != base);
+                let report = result
+                    .as_ref()
+                    .ok()
+                    .and_then(|&guild| self.report(guild, version, difficulty).ok());
+                ComparisonEntry {
+                    version,
+                    class,
+                    result,
+                    differs,
+                    report,
+                }
+            })
+            .collect();
+
+        if let Some(Err(error)) = entries
+            .iter()
+            .all(|entry| entry.result.is_err())
+            .then(|| entries[0].result.clone())
+        {
+            return Err(error);
+        }
+
+        Ok(Comparison {
+            name: name.to_string(),
+            baseline,
+            entries,
+        })
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let guild = match &entry.result {
+                Ok(guild) => guild.name().to_string(),
+                Err(error) => format!("({})", error),
+            };
+            write!(
+                f,
+                "{:<10} {:<10} {}{}",
+                entry.version.name(),
+                entry.class.map_or("-", |class| class.name()),
+                guild,
+                if entry.differs { " *" } else { "" }
+            )?;
+            match &entry.report {
+                Some(report) => writeln!(
+                    f,
+                    " (Common: {}, Rare: {})",
+                    report.common_drop, report.rare_drop
+                )?,
+                None => writeln!(f)?,
+            }
+        }
+        write!(f, "* differs from the V1, V2 and GameCube result")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate, DropTable, DropWeights, WeaponType};
+
+    #[test]
+    fn test_compare_matrix() {
+        let comparison = compare("Bob").unwrap();
+        assert_eq!(comparison.baseline, Some(Guild::Pinkal));
+        assert_eq!(comparison.entries.len(), 16);
+
+        for entry in &comparison.entries {
+            let guild = calculate("Bob", entry.version, entry.class).unwrap();
+            assert_eq!(entry.result, Ok(guild));
+            assert_eq!(entry.differs, guild != Guild::Pinkal);
+        }
+
+        assert!(!comparison.entries[0].differs);
+        assert!(!comparison.entries[1].differs);
+        assert_eq!(comparison.entries[2].version, GameVersion::GameCube);
+        assert!(!comparison.entries[2].differs);
+        // Bob is worth 15 in Blue Burst, and HUcaseal adds 4
+        let hucaseal = &comparison.entries[4 + 3];
+        assert_eq!(hucaseal.class, Some(CharacterClass::HUcaseal));
+        assert_eq!(hucaseal.result, Ok(Guild::Whitill));
+        assert!(hucaseal.differs);
+    }
+
+    #[test]
+    fn test_compare_display() {
+        let text = compare("Bob").unwrap().to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 17);
+        assert_eq!(
+            lines[0],
+            "V1         -          Pinkal (Common: Wands (13%), Rare: Rifles (1%))"
+        );
+        assert_eq!(
+            lines[2],
+            "GameCube   -          Pinkal (Common: Wands (13%), Rare: Rifles (1%))"
+        );
+        assert_eq!(
+            lines[4],
+            "BlueBurst  HUmar      Viridia * (Common: Partisans (10%), Rare: Slicers (1%))"
+        );
+        assert_eq!(lines[16], "* differs from the V1, V2 and GameCube result");
+    }
+
+    #[test]
+    fn test_compare_with_set() {
+        let mut weights = [1; 12];
+        weights[WeaponType::Canes as usize] = 89;
+        let table = DropTable {
+            guild: Guild::Pinkal,
+            version: Some(GameVersion::V2),
+            difficulty: None,
+            weights: DropWeights::new(weights).unwrap(),
+        };
+        let set = DropTableSet::new(vec![table], true).unwrap();
+
+        let comparison = set.compare("Bob", Difficulty::Ultimate).unwrap();
+        // V1 has no Ultimate, so there are no drops to report
+        assert_eq!(comparison.entries[0].result, Ok(Guild::Pinkal));
+        assert_eq!(comparison.entries[0].report, None);
+        let v2 = comparison.entries[1].report.unwrap();
+        assert_eq!(v2.difficulty, Some(Difficulty::Ultimate));
+        assert_eq!(v2.common_drop.weapon, "Canes");
+        let gamecube = comparison.entries[2].report.unwrap();
+        assert_eq!(gamecube.common_drop.weapon, "Wands");
+        assert_eq!(
+            comparison.to_string().lines().next().unwrap(),
+            "V1         -          Pinkal"
+        );
+    }
+
+    #[test]
+    fn test_compare_errors() {
+        assert_eq!(compare("").unwrap_err(), PsoidError::EmptyName);
+        assert_eq!(
+            compare("café").unwrap_err(),
+            PsoidError::NonAscii { index: 3, ch: 'é' }
+        );
+
+        // A tab is ASCII, but Blue Burst doesn't accept it
+        let comparison = compare("Bob\t").unwrap();
+        assert_eq!(comparison.baseline, Some(Guild::Purplenum));
+        assert_eq!(
+            comparison.entries[3].result,
+            Err(PsoidError::UnsupportedCharacter { index: 3, ch: '\t' })
+        );
+        assert!(!comparison.entries[3].differs);
+        assert_eq!(comparison.entries[3].report, None);
+    }
+}
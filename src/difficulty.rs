@@ -0,0 +1,108 @@
+//! Game difficulty levels
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Difficulty, DropTableSet, GameVersion, Guild};
+//!
+//! let difficulty: Difficulty = "very hard".parse().unwrap();
+//! assert_eq!(difficulty, Difficulty::VeryHard);
+//!
+//! // Ultimate was added in V2
+//! assert!(!Difficulty::Ultimate.is_available(GameVersion::V1));
+//! let set = DropTableSet::default();
+//! assert!(set.info(Guild::Redria, GameVersion::V1, Difficulty::Ultimate).is_err());
+//! ```
+
+use crate::{normalize, GameVersion, PsoidError};
+use std::fmt;
+use std::str::FromStr;
+
+/// A difficulty level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    Normal,
+    Hard,
+    VeryHard,
+    Ultimate,
+}
+
+impl Difficulty {
+    /// Every difficulty, easiest first
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::VeryHard,
+        Difficulty::Ultimate,
+    ];
+
+    /// Get the name of the difficulty as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::VeryHard => "Very Hard",
+            Difficulty::Ultimate => "Ultimate",
+        }
+    }
+
+    /// Whether the difficulty can be played in a game version
+    ///
+    /// Ultimate was added in V2; every other difficulty is in every version.
+    pub fn is_available(&self, version: GameVersion) -> bool {
+        !(*self == Difficulty::Ultimate && version == GameVersion::V1)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a difficulty, ignoring case, spaces, dashes and underscores
+///
+/// Accepts the full names and the short forms "n", "h", "vh" and "u"/"ult".
+impl FromStr for Difficulty {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match normalize(value).as_str() {
+            "normal" | "n" => Ok(Difficulty::Normal),
+            "hard" | "h" => Ok(Difficulty::Hard),
+            "veryhard" | "vh" => Ok(Difficulty::VeryHard),
+            "ultimate" | "ult" | "u" => Ok(Difficulty::Ultimate),
+            _ => Err(PsoidError::UnknownDifficulty(value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_difficulty() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.name().parse::<Difficulty>().unwrap(), difficulty);
+        }
+        for text in ["vh", "VeryHard", "very-hard", "VERY_HARD"] {
+            assert_eq!(text.parse::<Difficulty>().unwrap(), Difficulty::VeryHard);
+        }
+        assert_eq!("ult".parse::<Difficulty>().unwrap(), Difficulty::Ultimate);
+        assert_eq!(
+            "Insane".parse::<Difficulty>().unwrap_err(),
+            PsoidError::UnknownDifficulty("Insane".to_string())
+        );
+    }
+
+    #[test]
+    fn test_availability() {
+        for version in GameVersion::ALL {
+            for difficulty in Difficulty::ALL {
+                let expected = !(version == GameVersion::V1 && difficulty == Difficulty::Ultimate);
+                assert_eq!(difficulty.is_available(version), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,679 @@
+//! Custom weapon drop tables, e.g. a private server's rates
+//!
+//! A [`DropTableSet`] holds weapon weights per guild, optionally limited to
+//! one game version and/or difficulty. When a guild is looked up the most
+//! specific matching table wins; with `inherit` on (the default) anything
+//! the set doesn't cover falls back to the built-in tables, and with it off
+//! every guild needs a table that applies everywhere.
+//!
+//! The crate ships no per-difficulty data: the built-in tables are the
+//! published ones, the same on every difficulty, so reports only change with
+//! the difficulty when the caller supplies tables for it (from the game's
+//! `ItemPT` files, TOML, JSON or [`DropTable`] values).
+//!
+//! Every report and query that shows drop data can run against a set:
+//! [`DropTableSet::info`], [`name_report`](DropTableSet::name_report),
+//! [`compare`](DropTableSet::compare),
+//! [`process_roster`](DropTableSet::process_roster),
+//! [`plan_hunt`](DropTableSet::plan_hunt) and the ranking queries. The free
+//! functions of the same name use the built-in tables on Normal.
+//!
+//! Sets can be built from the game's own item pattern tables with
+//! [`DropTableSet::from_item_pt`], or loaded from TOML (the `toml` cargo
+//! feature) or JSON (the `json` feature). Every weapon type needs a weight:
+//!
+//! ```toml
+//! inherit = true
+//!
+//! [[tables]]
+//! guild = "Redria"
+//! difficulty = "ultimate"   # optional, as is version = "bb"
+//!
+//! [tables.weights]
+//! sabers = 10
+//! swords = 5
+//! # ... and the other ten weapon types
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Difficulty, DropTable, DropTableSet, DropWeights, GameVersion, Guild, WeaponType};
+//!
+//! let mut weights = [5; 12];
+//! weights[WeaponType::Slicers as usize] = 45;
+//! let table = DropTable {
+//!     guild: Guild::Redria,
+//!     version: None,
+//!     difficulty: Some(Difficulty::Ultimate),
+//!     weights: DropWeights::new(weights).unwrap(),
+//! };
+//! let set = DropTableSet::new(vec![table], true).unwrap();
+//!
+//! let info = set.info(Guild::Redria, GameVersion::BlueBurst, Difficulty::Ultimate).unwrap();
+//! assert_eq!(info.common_drop().percent(), Some(45));
+//! // Other difficulties and guilds keep the built-in rates
+//! let info = set.info(Guild::Redria, GameVersion::BlueBurst, Difficulty::Hard).unwrap();
+//! assert_eq!(info.drop_rates(), Guild::Redria.drop_rates());
+//! ```
+
+use crate::recommend::rank_by;
+use crate::{
+    calculate_with, CharacterClass, Difficulty, DropWeights, Episode, GameVersion, Guild,
+    GuildInfo, GuildReport, GuildScore, ItemPatternSet, NameReport, PsoidError, SectionIdAlgorithm,
+    WeaponType,
+};
+
+/// Weapon weights for one guild, optionally only in one version or difficulty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropTable {
+    pub guild: Guild,
+    /// `None` applies to every version
+    pub version: Option<GameVersion>,
+    /// `None` applies to every difficulty
+    pub difficulty: Option<Difficulty>,
+    pub weights: DropWeights,
+}
+
+impl DropTable {
+    fn matches(&self, guild: Guild, version: GameVersion, difficulty: Difficulty) -> bool {
+        self.guild == guild
+            && self.version.is_none_or(|v| v == version)
+            && self.difficulty.is_none_or(|d| d == difficulty)
+    }
+
+    /// Tables limited to a version beat ones limited to a difficulty, which
+    /// beat ones that apply everywhere
+    fn specificity(&self) -> u8 {
+        u8::from(self.version.is_some()) * 2 + u8::from(self.difficulty.is_some())
+    }
+}
+
+/// A set of drop tables that overrides or extends the built-in ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropTableSet {
+    tables: Vec<DropTable>,
+    inherit: bool,
+}
+
+/// The built-in tables and nothing else
+impl Default for DropTableSet {
+    fn default() -> Self {
+        DropTableSet {
+            tables: Vec::new(),
+            inherit: true,
+        }
+    }
+}
+
+impl DropTableSet {
+    /// Create a set, checking that it's complete
+    ///
+    /// Fails if two tables cover the same guild, version and difficulty, or
+    /// if `inherit` is off and a guild has no table that applies everywhere.
+    pub fn new(tables: Vec<DropTable>, inherit: bool) -> Result<DropTableSet, PsoidError> {
+        for (index, table) in tables.iter().enumerate() {
+            let duplicate = tables[..index].iter().any(|earlier| {
+                (earlier.guild, earlier.version, earlier.difficulty)
+                    == (table.guild, table.version, table.difficulty)
+            });
+            if duplicate {
+                return Err(PsoidError::InvalidDropTable(format!(
+                    "more than one table for {}{}{}",
+                    table.guild.name(),
+                    table
+                        .version
+                        .map_or(String::new(), |v| format!(" in {}", v)),
+                    table
+                        .difficulty
+                        .map_or(String::new(), |d| format!(" on {}", d)),
+                )));
+            }
+        }
+
+        if !inherit {
+            if let Some(guild) = Guild::ALL.into_iter().find(|&guild| {
+                !tables.iter().any(|table| {
+                    table.guild == guild && table.version.is_none() && table.difficulty.is_none()
+                })
+            }) {
+                return Err(PsoidError::MissingGuild(guild));
+            }
+        }
+
+        Ok(DropTableSet { tables, inherit })
+    }
+
+    /// Build a set from the game's item pattern tables for one version
+    ///
+    /// Each of `episode`'s tables becomes a table for `version` and its
+    /// difficulty. Guilds and difficulties the archive doesn't have fall back
+    /// to the built-in tables. Combine versions by passing the
+    /// [`tables`](DropTableSet::tables) of several sets to
+    /// [`DropTableSet::new`]. Fails if a table's weights are all zero.
+    pub fn from_item_pt(
+        set: &ItemPatternSet,
+        version: GameVersion,
+        episode: Episode,
+    ) -> Result<DropTableSet, PsoidError> {
+        let tables = set
+            .entries()
+            .iter()
+            .filter(|entry| entry.episode == episode)
+            .map(|entry| {
+                Ok(DropTable {
+                    guild: entry.guild,
+                    version: Some(version),
+                    difficulty: Some(entry.difficulty),
+                    weights: entry.pattern.drop_weights()?,
+                })
+            })
+            .collect::<Result<_, PsoidError>>()?;
+        DropTableSet::new(tables, true)
+    }
+
+    /// Get the tables in the set
+    pub fn tables(&self) -> &[DropTable] {
+        &self.tables
+    }
+
+    /// Whether guilds without a table fall back to the built-in ones
+    pub fn inherits(&self) -> bool {
+        self.inherit
+    }
+
+    /// Get the weights a guild uses in a version and difficulty
+    ///
+    /// Without a matching table this is [`Guild::drop_weights`], whatever the
+    /// difficulty.
+    pub fn weights(
+        &self,
+        guild: Guild,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> DropWeights {
+        self.tables
+            .iter()
+            .filter(|table| table.matches(guild, version, difficulty))
+            .max_by_key(|table| table.specificity())
+            .map_or_else(|| guild.drop_weights(), |table| table.weights)
+    }
+
+    /// Look up a guild's drop data in this set
+    ///
+    /// Fails if the difficulty isn't in that version (Ultimate in V1).
+    pub fn info(
+        &self,
+        guild: Guild,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<GuildInfo, PsoidError> {
+        GuildInfo::new(guild, version, self.weights(guild, version, difficulty))
+            .with_difficulty(difficulty)
+    }
+
+    /// Like [`Guild::report`], using this set's rates for a version and
+    /// difficulty
+    pub fn report(
+        &self,
+        guild: Guild,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<GuildReport, PsoidError> {
+        Ok(self.info(guild, version, difficulty)?.report())
+    }
+
+    /// Like [`rank_guilds`](crate::rank_guilds), using this set's rates
+    pub fn rank_guilds(
+        &self,
+        wishlist: &[(WeaponType, u32)],
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<Vec<GuildScore>, PsoidError> {
+        let infos = self.infos(version, difficulty)?;
+        Ok(rank_by(wishlist, |guild| {
+            infos[guild.id() as usize].drop_rates()
+        }))
+    }
+
+    /// Like [`Guild::best_for`], using this set's rates
+    pub fn best_for(
+        &self,
+        weapon: WeaponType,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<Vec<Guild>, PsoidError> {
+        let infos = self.infos(version, difficulty)?;
+        Ok(Guild::best_by(weapon, |guild| {
+            infos[guild.id() as usize].drop_rates()
+        }))
+    }
+
+    /// Like [`Guild::worst_for`], using this set's rates
+    pub fn worst_for(
+        &self,
+        weapon: WeaponType,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<Vec<Guild>, PsoidError> {
+        let infos = self.infos(version, difficulty)?;
+        Ok(Guild::worst_by(weapon, |guild| {
+            infos[guild.id() as usize].drop_rates()
+        }))
+    }
+
+    /// Like [`NameReport::new`], using this set's rates for a difficulty
+    ///
+    /// Fails if the name is invalid or the difficulty isn't in that version.
+    pub fn name_report(
+        &self,
+        name: &str,
+        version: GameVersion,
+        difficulty: Difficulty,
+        class: Option<CharacterClass>,
+    ) -> Result<NameReport, PsoidError> {
+        self.name_report_with(name, version, version.algorithm(), difficulty, class)
+    }
+
+    /// Like [`DropTableSet::name_report`], calculating the guild with
+    /// `algorithm` instead of the version's own
+    pub fn name_report_with(
+        &self,
+        name: &str,
+        version: GameVersion,
+        algorithm: &dyn SectionIdAlgorithm,
+        difficulty: Difficulty,
+        class: Option<CharacterClass>,
+    ) -> Result<NameReport, PsoidError> {
+        let guild = calculate_with(name, algorithm, class)?;
+        let info = self.info(guild, version, difficulty)?;
+        Ok(NameReport::from_info(name, class, &info))
+    }
+
+    fn infos(
+        &self,
+        version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<Vec<GuildInfo>, PsoidError> {
+        Guild::ALL
+            .into_iter()
+            .map(|guild| self.info(guild, version, difficulty))
+            .collect()
+    }
+}
+
+#[cfg(any(feature = "json", feature = "toml"))]
+mod parse {
+    use super::{DropTable, DropTableSet};
+    use crate::{DropWeights, PsoidError, WeaponType};
+    use std::collections::BTreeMap;
+
+    /// A set as written in a file, before names are checked
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct RawSet {
+        #[serde(default = "inherit_by_default")]
+        inherit: bool,
+        #[serde(default)]
+        tables: Vec<RawTable>,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct RawTable {
+        guild: String,
+        version: Option<String>,
+        difficulty: Option<String>,
+        weights: BTreeMap<String, u32>,
+    }
+
+    fn inherit_by_default() -> bool {
+        true
+    }
+
+    impl DropTableSet {
+        /// Read a set from TOML
+        #[cfg(feature = "toml")]
+        pub fn from_toml(input: &str) -> Result<DropTableSet, PsoidError> {
+            let raw: RawSet = toml::from_str(input)
+                .map_err(|e| PsoidError::InvalidDropTable(e.message().to_string()))?;
+            raw.validate()
+        }
+
+        /// Read a set from JSON
+        #[cfg(feature = "json")]
+        pub fn from_json(input: &str) -> Result<DropTableSet, PsoidError> {
+            let raw: RawSet = serde_json::from_str(input)
+                .map_err(|e| PsoidError::InvalidDropTable(e.to_string()))?;
+            raw.validate()
+        }
+    }
+
+    impl RawSet {
+        fn validate(self) -> Result<DropTableSet, PsoidError> {
+            let tables = self
+                .tables
+                .into_iter()
+                .map(RawTable::validate)
+                .collect::<Result<_, _>>()?;
+            DropTableSet::new(tables, self.inherit)
+        }
+    }
+
+    impl RawTable {
+        fn validate(self) -> Result<DropTable, PsoidError> {
+            let guild = self.guild.parse()?;
+            let mut weights: [Option<u32>; 12] = [None; 12];
+            for (name, weight) in self.weights {
+                let weapon: WeaponType = name.parse()?;
+                if weights[weapon as usize].replace(weight).is_some() {
+                    return Err(PsoidError::InvalidDropTable(format!(
+                        "{} has more than one weight for {}",
+                        self.guild, weapon
+                    )));
+                }
+            }
+            let mut complete = [0; 12];
+            for weapon in WeaponType::ALL {
+                complete[weapon as usize] =
+                    weights[weapon as usize].ok_or(PsoidError::MissingWeapon { guild, weapon })?;
+            }
+
+            Ok(DropTable {
+                guild,
+                version: self.version.as_deref().map(str::parse).transpose()?,
+                difficulty: self.difficulty.as_deref().map(str::parse).transpose()?,
+                weights: DropWeights::new(complete)?,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(
+        guild: Guild,
+        version: Option<GameVersion>,
+        difficulty: Option<Difficulty>,
+        rods: u32,
+    ) -> DropTable {
+        let mut weights = [1; 12];
+        weights[WeaponType::Rods as usize] = rods;
+        DropTable {
+            guild,
+            version,
+            difficulty,
+            weights: DropWeights::new(weights).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_most_specific_table_wins() {
+        let set = DropTableSet::new(
+            vec![
+                table(Guild::Oran, None, None, 89),
+                table(Guild::Oran, None, Some(Difficulty::Ultimate), 189),
+                table(Guild::Oran, Some(GameVersion::BlueBurst), None, 289),
+            ],
+            true,
+        )
+        .unwrap();
+
+        let rods = |version, difficulty| {
+            set.weights(Guild::Oran, version, difficulty)
+                .weight(WeaponType::Rods)
+        };
+        assert_eq!(rods(GameVersion::V2, Difficulty::Normal), 89);
+        assert_eq!(rods(GameVersion::V2, Difficulty::Ultimate), 189);
+        assert_eq!(rods(GameVersion::BlueBurst, Difficulty::Ultimate), 289);
+
+        let info = |difficulty| {
+            set.info(Guild::Oran, GameVersion::V2, difficulty)
+                .unwrap()
+                .report()
+        };
+        let (hard, ultimate) = (info(Difficulty::Hard), info(Difficulty::Ultimate));
+        assert_eq!(hard.difficulty, Some(Difficulty::Hard));
+        assert_eq!(ultimate.difficulty, Some(Difficulty::Ultimate));
+        assert_eq!((hard.drop_rates.rods, ultimate.drop_rates.rods), (89, 94));
+        assert_eq!(
+            set.weights(Guild::Skyly, GameVersion::V2, Difficulty::Normal),
+            Guild::Skyly.drop_weights()
+        );
+        assert_eq!(
+            set.info(Guild::Oran, GameVersion::V1, Difficulty::Ultimate)
+                .unwrap_err(),
+            PsoidError::UnavailableDifficulty {
+                version: GameVersion::V1,
+                difficulty: Difficulty::Ultimate
+            }
+        );
+    }
+
+    #[test]
+    fn test_queries_use_the_set() {
+        let set = DropTableSet::new(vec![table(Guild::Pinkal, None, None, 89)], true).unwrap();
+        let (version, difficulty) = (GameVersion::V1, Difficulty::Normal);
+
+        assert_eq!(
+            set.best_for(WeaponType::Rods, version, difficulty).unwrap(),
+            [Guild::Pinkal]
+        );
+        assert_eq!(
+            set.worst_for(WeaponType::Rods, version, difficulty)
+                .unwrap(),
+            Guild::worst_for(WeaponType::Rods)
+        );
+        let ranking = set
+            .rank_guilds(&[(WeaponType::Rods, 1)], version, difficulty)
+            .unwrap();
+        assert_eq!((ranking[0].guild, ranking[0].score), (Guild::Pinkal, 89));
+
+        // "foobar" is Bluefull in V1, which the set leaves alone
+        let report = set
+            .name_report("foobar", version, difficulty, None)
+            .unwrap();
+        assert_eq!(report, NameReport::new("foobar", version, None).unwrap());
+        // "Bob" is Pinkal, whose Hard report uses the set's table
+        let report = set
+            .name_report("Bob", version, Difficulty::Hard, None)
+            .unwrap();
+        assert_eq!(report.guild.difficulty, Some(Difficulty::Hard));
+        assert_eq!(report.guild.drop_rates.rods, 89);
+        assert_eq!(
+            set.name_report("Bob", version, Difficulty::Ultimate, None)
+                .unwrap_err(),
+            PsoidError::UnavailableDifficulty {
+                version,
+                difficulty: Difficulty::Ultimate
+            }
+        );
+        assert_eq!(
+            set.report(Guild::Pinkal, version, Difficulty::Hard)
+                .unwrap(),
+            report.guild
+        );
+
+        let builtin = DropTableSet::default();
+        assert_eq!(
+            builtin
+                .rank_guilds(&[(WeaponType::Rods, 1)], version, difficulty)
+                .unwrap(),
+            crate::rank_guilds(&[(WeaponType::Rods, 1)])
+        );
+    }
+
+    #[test]
+    fn test_difficulties_come_from_the_set() {
+        let set = DropTableSet::new(
+            vec![
+                table(Guild::Oran, None, Some(Difficulty::Hard), 30),
+                table(Guild::Oran, None, Some(Difficulty::Ultimate), 60),
+            ],
+            true,
+        )
+        .unwrap();
+        let report = |difficulty| {
+            set.report(Guild::Oran, GameVersion::V2, difficulty)
+                .unwrap()
+        };
+
+        let (hard, ultimate) = (report(Difficulty::Hard), report(Difficulty::Ultimate));
+        assert_ne!(hard, ultimate);
+        assert_eq!(hard.difficulty, Some(Difficulty::Hard));
+        assert_eq!(hard.drop_rates.rods, 73);
+        assert_eq!(ultimate.difficulty, Some(Difficulty::Ultimate));
+        assert_eq!(ultimate.drop_rates.rods, 85);
+        // No Normal table was supplied, so Normal is the published table
+        assert_eq!(report(Difficulty::Normal), Guild::Oran.report());
+    }
+
+    #[test]
+    fn test_from_item_pt() {
+        let afs = ItemPatternSet::from_afs(include_bytes!("../tests/fixtures/ItemPT.afs")).unwrap();
+        let gsl = ItemPatternSet::from_gsl(include_bytes!("../tests/fixtures/ItemPT.gsl")).unwrap();
+        let v2 = DropTableSet::from_item_pt(&afs, GameVersion::V2, Episode::I).unwrap();
+        let gc = DropTableSet::from_item_pt(&gsl, gsl.version(), Episode::I).unwrap();
+        assert_eq!(v2.tables().len(), 2);
+        // Only the Episode I table is taken from the GSL
+        assert_eq!(gc.tables().len(), 1);
+
+        let set = DropTableSet::new([v2.tables(), gc.tables()].concat(), true).unwrap();
+        let sabers = |version, difficulty| {
+            set.weights(Guild::Viridia, version, difficulty)
+                .weight(WeaponType::Sabers)
+        };
+        assert_eq!(sabers(GameVersion::V2, Difficulty::Normal), 24);
+        assert_eq!(sabers(GameVersion::GameCube, Difficulty::Normal), 30);
+        assert_eq!(
+            sabers(GameVersion::BlueBurst, Difficulty::Normal),
+            Guild::Viridia.drop_weights().weight(WeaponType::Sabers)
+        );
+        // Neither archive has a Hard table, so the built-in one is used
+        assert_eq!(
+            set.weights(Guild::Viridia, GameVersion::V2, Difficulty::Hard),
+            Guild::Viridia.drop_weights()
+        );
+        assert_eq!(
+            set.weights(Guild::Greennill, GameVersion::V2, Difficulty::Normal)
+                .weight(WeaponType::Rifles),
+            25
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            DropTableSet::new(vec![table(Guild::Viridia, None, None, 1)], false).unwrap_err(),
+            PsoidError::MissingGuild(Guild::Greennill)
+        );
+        let every_guild: Vec<DropTable> = Guild::ALL
+            .into_iter()
+            .map(|guild| table(guild, None, None, 1))
+            .collect();
+        assert!(DropTableSet::new(every_guild, false).is_ok());
+
+        let twice = vec![
+            table(Guild::Redria, Some(GameVersion::V2), None, 1),
+            table(Guild::Redria, Some(GameVersion::V2), None, 2),
+        ];
+        assert_eq!(
+            DropTableSet::new(twice, true).unwrap_err(),
+            PsoidError::InvalidDropTable("more than one table for Redria in V2".to_string())
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml() {
+        let set = DropTableSet::from_toml(
+            r#"
+            [[tables]]
+            guild = "redria"
+            version = "bb"
+
+            [tables.weights]
+            sabers = 1
+            swords = 1
+            daggers = 1
+            partisans = 1
+            slicers = 96
+            handguns = 0
+            rifles = 0
+            machineguns = 0
+            shotguns = 0
+            canes = 0
+            rods = 0
+            wands = 0
+
+            [[tables]]
+            guild = "redria"
+            version = "bb"
+            difficulty = "ultimate"
+
+            [tables.weights]
+            sabers = 1
+            swords = 1
+            daggers = 1
+            partisans = 1
+            slicers = 0
+            handguns = 0
+            rifles = 0
+            machineguns = 0
+            shotguns = 0
+            canes = 96
+            rods = 0
+            wands = 0
+            "#,
+        )
+        .unwrap();
+        let info = set
+            .info(Guild::Redria, GameVersion::BlueBurst, Difficulty::Hard)
+            .unwrap();
+        assert_eq!(info.common_drop().percent(), Some(96));
+        let ultimate = set
+            .report(Guild::Redria, GameVersion::BlueBurst, Difficulty::Ultimate)
+            .unwrap();
+        assert_eq!(ultimate.common_drop.weapon, "Canes");
+        assert_ne!(ultimate, info.report());
+        assert!(set.inherits());
+
+        assert_eq!(
+            DropTableSet::from_toml("inherit = false").unwrap_err(),
+            PsoidError::MissingGuild(Guild::Viridia)
+        );
+        assert!(matches!(
+            DropTableSet::from_toml("rates = 1").unwrap_err(),
+            PsoidError::InvalidDropTable(_)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json() {
+        let missing_rods = r#"{"tables": [{"guild": "Oran", "weights": {
+            "sabers": 1, "swords": 1, "daggers": 1, "partisans": 1, "slicers": 1, "handguns": 1,
+            "rifles": 1, "machineguns": 1, "shotguns": 1, "canes": 1, "wands": 1}}]}"#;
+        assert_eq!(
+            DropTableSet::from_json(missing_rods).unwrap_err(),
+            PsoidError::MissingWeapon {
+                guild: Guild::Oran,
+                weapon: WeaponType::Rods
+            }
+        );
+
+        let unknown = r#"{"tables": [{"guild": "Oran", "weights": {"lances": 1}}]}"#;
+        assert_eq!(
+            DropTableSet::from_json(unknown).unwrap_err(),
+            PsoidError::UnknownWeapon("lances".to_string())
+        );
+
+        let twice = r#"{"tables": [{"guild": "Oran", "weights": {"rod": 1, "Rods": 2}}]}"#;
+        assert_eq!(
+            DropTableSet::from_json(twice).unwrap_err(),
+            PsoidError::InvalidDropTable("Oran has more than one weight for Rods".to_string())
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Game episodes
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Episode, GameVersion};
+//!
+//! let episode: Episode = "ep2".parse().unwrap();
+//! assert_eq!(episode, Episode::II);
+//! assert!(!episode.is_available(GameVersion::V2));
+//! assert!(episode.is_available(GameVersion::GameCube));
+//! ```
+
+use crate::{normalize, GameVersion, PsoidError};
+use std::fmt;
+use std::str::FromStr;
+
+/// An episode of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Episode {
+    I,
+    II,
+    IV,
+}
+
+impl Episode {
+    /// Every episode, in release order
+    pub const ALL: [Episode; 3] = [Episode::I, Episode::II, Episode::IV];
+
+    /// Get the name of the episode as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Episode::I => "Episode I",
+            Episode::II => "Episode II",
+            Episode::IV => "Episode IV",
+        }
+    }
+
+    /// Whether the episode can be played in a game version
+    ///
+    /// V1 and V2 only have Episode I, Episode II arrived with the GameCube
+    /// release, and Episode IV is Blue Burst only.
+    pub fn is_available(&self, version: GameVersion) -> bool {
+        match self {
+            Episode::I => true,
+            Episode::II => matches!(version, GameVersion::GameCube | GameVersion::BlueBurst),
+            Episode::IV => version == GameVersion::BlueBurst,
+        }
+    }
+}
+
+impl fmt::Display for Episode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse an episode, ignoring case, spaces, dashes and underscores
+///
+/// Accepts "1"/"I"/"ep1"/"Episode I" and the same forms for II and IV.
+impl FromStr for Episode {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized = normalize(value);
+        let number = normalized
+            .strip_prefix("episode")
+            .or_else(|| normalized.strip_prefix("ep"))
+            .unwrap_or(&normalized);
+        match number {
+            "1" | "i" => Ok(Episode::I),
+            "2" | "ii" => Ok(Episode::II),
+            "4" | "iv" => Ok(Episode::IV),
+            _ => Err(PsoidError::UnknownEpisode(value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_episode() {
+        for episode in Episode::ALL {
+            assert_eq!(episode.name().parse::<Episode>().unwrap(), episode);
+        }
+        for text in ["1", "I", "ep1", "Ep. 1", "episode i"] {
+            assert_eq!(text.parse::<Episode>().unwrap(), Episode::I);
+        }
+        assert_eq!("EP4".parse::<Episode>().unwrap(), Episode::IV);
+        assert_eq!(
+            "3".parse::<Episode>().unwrap_err(),
+            PsoidError::UnknownEpisode("3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_availability() {
+        assert!(GameVersion::ALL
+            .iter()
+            .all(|&version| Episode::I.is_available(version)));
+        assert!(!Episode::II.is_available(GameVersion::V1));
+        assert!(Episode::II.is_available(GameVersion::BlueBurst));
+        assert!(!Episode::IV.is_available(GameVersion::GameCube));
+        assert!(Episode::IV.is_available(GameVersion::BlueBurst));
+    }
+}
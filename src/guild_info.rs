@@ -0,0 +1,270 @@
+//! Guild data for a specific game version and difficulty
+//!
+//! A [`GuildInfo`] holds the weapon weights a guild uses in one version and
+//! difficulty. With the guild's built-in weights it reports the published
+//! common and rare drops; with weights loaded from a table it derives them
+//! from the weights.
+//!
+//! The crate ships no per-version or per-difficulty data. The item tables
+//! changed between releases, but no verified copy of each release's tables is
+//! bundled, so a `GuildInfo` built from [`Guild::drop_weights`] is the same for
+//! V1, V2, GameCube and Blue Burst. Real per-version and per-difficulty drops
+//! have to be supplied by the caller, through a
+//! [`DropTableSet`](crate::DropTableSet) with tables limited to a version or
+//! difficulty (for example one read from the game's `ItemPT` files).
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{DropWeights, GameVersion, Guild, GuildInfo, WeaponType};
+//!
+//! let mut weights = [5; 12];
+//! weights[WeaponType::Partisans as usize] = 45;
+//! let weights = DropWeights::new(weights).unwrap();
+//!
+//! let info = GuildInfo::new(Guild::Viridia, GameVersion::V1, weights);
+//! assert_eq!(info.common_drop().weapon(), Some(WeaponType::Partisans));
+//! assert_eq!(info.common_drop().percent(), Some(45));
+//! ```
+
+use crate::{
+    Difficulty, DropRates, DropWeights, GameVersion, Guild, PsoidError, WeaponDrop, WeaponType,
+};
+
+/// A guild's drop data in one game version and difficulty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuildInfo {
+    pub guild: Guild,
+    pub version: GameVersion,
+    pub difficulty: Difficulty,
+    weights: DropWeights,
+}
+
+impl GuildInfo {
+    /// Bundle a guild's weapon weights in a version, on Normal
+    ///
+    /// `version` is only recorded; the weights decide the drops.
+    pub fn new(guild: Guild, version: GameVersion, weights: DropWeights) -> GuildInfo {
+        GuildInfo {
+            guild,
+            version,
+            difficulty: Difficulty::Normal,
+            weights,
+        }
+    }
+
+    /// Move to another difficulty, keeping the weights
+    ///
+    /// Fails if the difficulty isn't in this version (Ultimate in V1).
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Result<GuildInfo, PsoidError> {
+        if !difficulty.is_available(self.version) {
+            return Err(PsoidError::UnavailableDifficulty {
+                version: self.version,
+                difficulty,
+            });
+        }
+        Ok(GuildInfo { difficulty, ..self })
+    }
+
+    /// Replace the weapon weights, e.g. with a table read from game files
+    pub fn with_weights(self, weights: DropWeights) -> GuildInfo {
+        GuildInfo { weights, ..self }
+    }
+
+    /// Get the relative weight of each weapon type in this version and difficulty
+    pub fn drop_weights(&self) -> DropWeights {
+        self.weights
+    }
+
+    /// Get the weapon drop rates in this version and difficulty, in whole
+    /// percentages
+    pub fn drop_rates(&self) -> DropRates {
+        self.weights.percentages()
+    }
+
+    /// Get the most common drop in this table
+    ///
+    /// With the guild's own [`Guild::drop_weights`] this is the published
+    /// [`Guild::common_drop`]. Any other table gives the weapon with the
+    /// highest weight; if several share it, the one [`Guild::common_drop`]
+    /// names wins when it's among them, otherwise the first in
+    /// [`WeaponType::ALL`] order. A table where every weapon has the same
+    /// weight gives [`WeaponDrop::AllEqual`].
+    pub fn common_drop(&self) -> WeaponDrop {
+        if self.is_published() {
+            return self.guild.common_drop();
+        }
+        let highest = self.weight_range().1;
+        self.drop_with_weight(highest, self.guild.common_weapon())
+    }
+
+    /// Get the rarest drop in this table
+    ///
+    /// With the guild's own [`Guild::drop_weights`] this is the published
+    /// [`Guild::rare_drop`]. Any other table gives the weapon with the lowest
+    /// weight, with ties settled like [`GuildInfo::common_drop`] using
+    /// [`Guild::rare_drop`].
+    pub fn rare_drop(&self) -> WeaponDrop {
+        if self.is_published() {
+            return self.guild.rare_drop();
+        }
+        let lowest = self.weight_range().0;
+        self.drop_with_weight(lowest, self.guild.rare_weapon())
+    }
+
+    /// Whether the weights are the guild's published table rather than one
+    /// from a [`DropTableSet`](crate::DropTableSet)
+    pub(crate) fn is_published(&self) -> bool {
+        self.weights == self.guild.drop_weights()
+    }
+
+    fn weight_range(&self) -> (u32, u32) {
+        let weights = WeaponType::ALL.map(|weapon| self.weights.weight(weapon));
+        let lowest = weights.into_iter().min().unwrap_or_default();
+        let highest = weights.into_iter().max().unwrap_or_default();
+        (lowest, highest)
+    }
+
+    fn drop_with_weight(&self, weight: u32, preferred: Option<WeaponType>) -> WeaponDrop {
+        let (lowest, highest) = self.weight_range();
+        if lowest == highest {
+            return WeaponDrop::AllEqual;
+        }
+        let has_weight = |weapon: &WeaponType| self.weights.weight(*weapon) == weight;
+        let weapon = preferred
+            .filter(has_weight)
+            .or_else(|| WeaponType::ALL.into_iter().find(has_weight));
+        WeaponDrop::from_rates(weapon, &self.drop_rates())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WeaponType;
+
+    #[test]
+    fn test_published_weights_give_published_drops() {
+        for guild in Guild::ALL {
+            for version in GameVersion::ALL {
+                let info = GuildInfo::new(guild, version, guild.drop_weights());
+                assert_eq!(info.common_drop(), guild.common_drop(), "{}", guild);
+                assert_eq!(info.rare_drop(), guild.rare_drop(), "{}", guild);
+            }
+        }
+
+        let published = |guild: Guild| {
+            let info = GuildInfo::new(guild, GameVersion::V2, guild.drop_weights());
+            (info.common_drop(), info.rare_drop())
+        };
+        let weapon = |weapon, percent| WeaponDrop::Weapon { weapon, percent };
+        assert_eq!(
+            published(Guild::Viridia),
+            (
+                weapon(WeaponType::Partisans, 10),
+                weapon(WeaponType::Slicers, 1)
+            )
+        );
+        assert_eq!(
+            published(Guild::Purplenum),
+            (
+                weapon(WeaponType::Machineguns, 13),
+                weapon(WeaponType::Daggers, 10)
+            )
+        );
+        assert_eq!(
+            published(Guild::Yellowboze),
+            (WeaponDrop::AllEqual, WeaponDrop::AllEqual)
+        );
+    }
+
+    #[test]
+    fn test_drops_follow_loaded_weights() {
+        // Shotguns weigh the most; Swords, Rifles and Machineguns tie for the
+        // least and Swords comes first
+        let weights = DropWeights::new([9, 6, 7, 10, 8, 9, 6, 6, 11, 9, 7, 7]).unwrap();
+        let info = GuildInfo::new(Guild::Viridia, GameVersion::V1, weights);
+        assert_eq!(info.common_drop().weapon(), Some(WeaponType::Shotguns));
+        assert_eq!(info.rare_drop().weapon(), Some(WeaponType::Swords));
+
+        let even = DropWeights::new([3; 12]).unwrap();
+        let info = GuildInfo::new(Guild::Viridia, GameVersion::V1, even);
+        assert_eq!(info.common_drop(), WeaponDrop::AllEqual);
+        assert_eq!(info.rare_drop(), WeaponDrop::AllEqual);
+    }
+
+    #[test]
+    fn test_difficulties() {
+        let mut weights = [5; 12];
+        weights[WeaponType::Swords as usize] = 45;
+        let ultimate_weights = DropWeights::new(weights).unwrap();
+
+        let normal = GuildInfo::new(Guild::Skyly, GameVersion::V2, Guild::Skyly.drop_weights());
+        assert_eq!(normal.difficulty, Difficulty::Normal);
+        let ultimate = normal
+            .with_difficulty(Difficulty::Ultimate)
+            .unwrap()
+            .with_weights(ultimate_weights);
+        assert_eq!(ultimate.difficulty, Difficulty::Ultimate);
+        assert_eq!(normal.drop_rates().swords, 13);
+        assert_eq!(ultimate.drop_rates().swords, 45);
+        assert_eq!(normal.rare_drop().percent(), Some(1));
+        assert_eq!(ultimate.rare_drop().percent(), Some(5));
+
+        for version in GameVersion::ALL {
+            let info = GuildInfo::new(Guild::Skyly, version, Guild::Skyly.drop_weights());
+            for difficulty in Difficulty::ALL {
+                let moved = info.with_difficulty(difficulty);
+                if difficulty.is_available(version) {
+                    assert_eq!(moved.unwrap().difficulty, difficulty);
+                } else {
+                    assert_eq!(
+                        moved.unwrap_err(),
+                        PsoidError::UnavailableDifficulty {
+                            version,
+                            difficulty
+                        }
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_weights() {
+        let mut weights = [5; 12];
+        weights[WeaponType::Slicers as usize] = 50;
+        weights[WeaponType::Daggers as usize] = 0;
+        let weights = DropWeights::new(weights).unwrap();
+
+        let published = GuildInfo::new(
+            Guild::Redria,
+            GameVersion::BlueBurst,
+            Guild::Redria.drop_weights(),
+        );
+        let info = published.with_weights(weights);
+        assert_eq!(info.version, GameVersion::BlueBurst);
+        assert_eq!(info.drop_weights(), weights);
+        assert_eq!(info.drop_rates().slicers, 50);
+        assert_eq!(
+            info.common_drop(),
+            WeaponDrop::Weapon {
+                weapon: WeaponType::Slicers,
+                percent: 50
+            }
+        );
+        assert_eq!(
+            info.rare_drop(),
+            WeaponDrop::Weapon {
+                weapon: WeaponType::Daggers,
+                percent: 0
+            }
+        );
+        assert_eq!(published.common_drop().weapon(), Some(WeaponType::Slicers));
+
+        // Yellowboze's published drops are All Equal, but these weights aren't
+        let info = GuildInfo::new(Guild::Yellowboze, GameVersion::V2, weights);
+        assert_eq!(info.common_drop().weapon(), Some(WeaponType::Slicers));
+        assert_eq!(info.rare_drop().weapon(), Some(WeaponType::Daggers));
+    }
+}
@@ -0,0 +1,402 @@
+//! Plan where to hunt for a wishlist of rare items
+//!
+//! A hunt is one clear of one area with one Section ID at one difficulty.
+//! Its score is the expected number of wishlist items it drops: every enemy
+//! in the area contributes `count * rate` for its rare, and the area's boxes
+//! contribute `boxes * rate` for each box rare.
+//!
+//! Enemy counts come from a [`SpawnTable`], read from CSV with the columns
+//! `area,enemy,count`. Rows whose enemy is `box` give the number of boxes.
+//! Like the rare tables, no spawn data is bundled; the counts come from you.
+//!
+//! [`DropTableSet::plan_hunt`] plans for one game version instead, leaving
+//! out difficulties and episodes it doesn't have, and adds each Section ID's
+//! weapon drops from the set.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{plan_hunt, Area, BoxRareTable, Difficulty, Guild, RareTable, SpawnTable};
+//!
+//! let rares = RareTable::from_csv(
+//!     "episode,difficulty,enemy,guild,item,rate\n\
+//!      1,ultimate,Booma,Redria,Item A,1/100\n",
+//! )
+//! .unwrap();
+//! let spawns = SpawnTable::from_csv("area,enemy,count\nForest 1,Booma,20\n").unwrap();
+//!
+//! let plans = plan_hunt(&["Item A"], &rares, &BoxRareTable::default(), &spawns);
+//! assert_eq!(plans[0].guild, Guild::Redria);
+//! assert_eq!(plans[0].difficulty, Difficulty::Ultimate);
+//! assert_eq!(plans[0].area, Area::Forest1);
+//! assert_eq!(plans[0].expected, 0.2);
+//! ```
+
+use crate::rares::read_csv;
+use crate::{
+    Area, BoxRareTable, Difficulty, DropTableSet, Episode, GameVersion, Guild, GuildReport,
+    Probability, PsoidError, RareTable,
+};
+use std::fmt;
+
+/// How many of each enemy, and how many boxes, an area has per run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpawnTable {
+    spawns: Vec<(Area, String, u32)>,
+}
+
+impl SpawnTable {
+    /// Columns required in a spawn table CSV header
+    pub const COLUMNS: [&'static str; 3] = ["area", "enemy", "count"];
+
+    /// The enemy name used for an area's boxes
+    pub const BOX: &'static str = "box";
+
+    /// Create a table from `(area, enemy, count)` entries
+    pub fn new(spawns: Vec<(Area, String, u32)>) -> SpawnTable {
+        SpawnTable { spawns }
+    }
+
+    /// Read a table from CSV with a header row
+    pub fn from_csv(input: &str) -> Result<SpawnTable, PsoidError> {
+        let spawns = read_csv(input, Self::COLUMNS, |row| {
+            Ok((row.parse(0)?, row.get(1)?.to_string(), row.number(2)?))
+        })?;
+        Ok(SpawnTable { spawns })
+    }
+
+    /// Get every area with spawn data, in play order
+    pub fn areas(&self) -> Vec<Area> {
+        let mut areas: Vec<Area> = self.spawns.iter().map(|(area, _, _)| *area).collect();
+        areas.sort();
+        areas.dedup();
+        areas
+    }
+
+    /// Get each enemy in an area with how many appear per run
+    pub fn enemies_in(&self, area: Area) -> Vec<(&str, u32)> {
+        self.spawns
+            .iter()
+            .filter(|(spawn_area, enemy, _)| *spawn_area == area && !is_box(enemy))
+            .map(|(_, enemy, count)| (enemy.as_str(), *count))
+            .collect()
+    }
+
+    /// Get the number of boxes in an area per run
+    pub fn boxes_in(&self, area: Area) -> u32 {
+        self.spawns
+            .iter()
+            .filter(|(spawn_area, enemy, _)| *spawn_area == area && is_box(enemy))
+            .map(|(_, _, count)| count)
+            .sum()
+    }
+}
+
+fn is_box(enemy: &str) -> bool {
+    enemy.eq_ignore_ascii_case(SpawnTable::BOX)
+}
+
+/// Where a wishlist item comes from in a hunt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropSource {
+    /// An enemy, by name
+    Enemy(String),
+    /// The area's boxes
+    Box,
+}
+
+impl fmt::Display for DropSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropSource::Enemy(name) => f.write_str(name),
+            DropSource::Box => f.write_str("Boxes"),
+        }
+    }
+}
+
+/// How much one enemy or the boxes add to a hunt's score
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+    pub source: DropSource,
+    pub item: String,
+    /// Enemies (or boxes) per run
+    pub count: u32,
+    pub rate: Probability,
+    /// `count * rate`
+    pub expected: f64,
+}
+
+/// One Section ID, difficulty and area to hunt in, with its expected drops
+#[derive(Debug, Clone, PartialEq)]
+pub struct HuntPlan {
+    pub guild: Guild,
+    pub episode: Episode,
+    pub difficulty: Difficulty,
+    pub area: Area,
+    /// Expected wishlist items per run
+    pub expected: f64,
+    /// Largest contribution first
+    pub contributions: Vec<Contribution>,
+    /// The Section ID's weapon drops, if the plan came from a [`DropTableSet`]
+    pub weapons: Option<GuildReport>,
+}
+
+/// Rank every Section ID, difficulty and area by expected wishlist drops
+///
+/// Item names are matched ignoring case. Only areas in `spawns` are
+/// considered, and hunts that can't drop anything on the wishlist are left
+/// out. Plans are returned best first; ties keep guild, difficulty and area
+/// order.
+pub fn plan_hunt(
+    wishlist: &[&str],
+    enemies: &RareTable,
+    boxes: &BoxRareTable,
+    spawns: &SpawnTable,
+) -> Vec<HuntPlan> {
+    let wanted = |item: &str| {
+        wishlist
+            .iter()
+            .any(|wanted| wanted.trim().eq_ignore_ascii_case(item))
+    };
+
+    let mut plans = Vec::new();
+    for guild in Guild::ALL {
+        for difficulty in Difficulty::ALL {
+            for area in spawns.areas() {
+                let episode = area.episode();
+                let mut contributions = Vec::new();
+
+                for (enemy, count) in spawns.enemies_in(area) {
+                    for drop in enemies.rares_for(guild, difficulty) {
+                        if drop.episode == episode
+                            && drop.enemy.eq_ignore_ascii_case(enemy)
+                            && wanted(&drop.item)
+                        {
+                            contributions.push(contribution(
+                                DropSource::Enemy(drop.enemy.clone()),
+                                &drop.item,
+                                count,
+                                drop.rate,
+                            ));
+                        }
+                    }
+                }
+
+                let box_count = spawns.boxes_in(area);
+                for rare in boxes.box_rares(area, difficulty, guild) {
+                    if box_count > 0 && wanted(&rare.item) {
+                        contributions.push(contribution(
+                            DropSource::Box,
+                            &rare.item,
+                            box_count,
+                            rare.rate,
+                        ));
+                    }
+                }
+
+                if contributions.is_empty() {
+                    continue;
+                }
+                contributions.sort_by(|a, b| b.expected.total_cmp(&a.expected));
+                plans.push(HuntPlan {
+                    guild,
+                    episode,
+                    difficulty,
+                    area,
+                    expected: contributions.iter().map(|entry| entry.expected).sum(),
+                    contributions,
+                    weapons: None,
+                });
+            }
+        }
+    }
+
+    plans.sort_by(|a, b| b.expected.total_cmp(&a.expected));
+    plans
+}
+
+impl DropTableSet {
+    /// Like [`plan_hunt`], for one game version with this set's weapon drops
+    ///
+    /// Difficulties and episodes the version doesn't have are left out.
+    pub fn plan_hunt(
+        &self,
+        wishlist: &[&str],
+        enemies: &RareTable,
+        boxes: &BoxRareTable,
+        spawns: &SpawnTable,
+        version: GameVersion,
+    ) -> Vec<HuntPlan> {
+        let mut plans = plan_hunt(wishlist, enemies, boxes, spawns);
+        plans.retain(|plan| {
+            plan.difficulty.is_available(version) && plan.episode.is_available(version)
+        });
+        for plan in &mut plans {
+            plan.weapons = self.report(plan.guild, version, plan.difficulty).ok();
+        }
+        plans
+    }
+}
+
+fn contribution(source: DropSource, item: &str, count: u32, rate: Probability) -> Contribution {
+    Contribution {
+        source,
+        item: item.to_string(),
+        count,
+        rate,
+        expected: f64::from(count) * rate.as_f64(),
+    }
+}
+
+impl fmt::Display for HuntPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ({}): {:.4} expected per run",
+            self.guild.name(),
+            self.difficulty,
+            self.area,
+            self.episode,
+            self.expected
+        )?;
+        if let Some(weapons) = &self.weapons {
+            write!(
+                f,
+                "\n  Weapons: Common: {}, Rare: {}",
+                weapons.common_drop, weapons.rare_drop
+            )?;
+        }
+        for entry in &self.contributions {
+            write!(
+                f,
+                "\n  {} x {}: {} at {} = {:.4}",
+                entry.count, entry.source, entry.item, entry.rate, entry.expected
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> (RareTable, BoxRareTable, SpawnTable) {
+        let enemies = RareTable::from_csv(
+            "episode,difficulty,enemy,guild,item,rate\n\
+             1,hard,Booma,Skyly,Item A,1/100\n\
+             1,hard,Dubchic,Skyly,Item A,1/50\n\
+             1,hard,Booma,Oran,Item A,1/400\n\
+             1,hard,Dubchic,Oran,Item B,1/10\n\
+             2,hard,Booma,Skyly,Item A,1/2\n",
+        )
+        .unwrap();
+        let boxes = BoxRareTable::from_csv(
+            "area,difficulty,guild,item,rate\n\
+             Mines 1,hard,Oran,Item A,1/1000\n\
+             Forest 1,hard,Oran,Item A,1/1000\n",
+        )
+        .unwrap();
+        let spawns = SpawnTable::from_csv(
+            "area,enemy,count\n\
+             Forest 1,Booma,20\n\
+             Forest 1,box,10\n\
+             Mines 1,Dubchic,15\n\
+             Mines 1,Box,40\n",
+        )
+        .unwrap();
+        (enemies, boxes, spawns)
+    }
+
+    #[test]
+    fn test_spawn_table() {
+        let (_, _, spawns) = tables();
+        assert_eq!(spawns.areas(), [Area::Forest1, Area::Mines1]);
+        assert_eq!(spawns.enemies_in(Area::Forest1), [("Booma", 20)]);
+        assert_eq!(spawns.boxes_in(Area::Mines1), 40);
+        assert_eq!(spawns.boxes_in(Area::Caves1), 0);
+        assert_eq!(
+            SpawnTable::from_csv("area,enemy,count\nForest 1,Booma,lots\n").unwrap_err(),
+            PsoidError::MalformedRow("line 2: count must be a number, got lots".to_string())
+        );
+    }
+
+    #[test]
+    fn test_plan_hunt() {
+        let (enemies, boxes, spawns) = tables();
+        let plans = plan_hunt(&["item a"], &enemies, &boxes, &spawns);
+
+        let ranking: Vec<(Guild, Area, String)> = plans
+            .iter()
+            .map(|plan| (plan.guild, plan.area, format!("{:.2}", plan.expected)))
+            .collect();
+        // Skyly: 15 Dubchic at 1/50 = 0.3, 20 Booma at 1/100 = 0.2 (the
+        // Episode II Booma rare doesn't count in Forest 1)
+        // Oran: 20 Booma at 1/400 + 10 boxes at 1/1000 = 0.06, 40 boxes at 1/1000 = 0.04
+        assert_eq!(
+            ranking,
+            [
+                (Guild::Skyly, Area::Mines1, "0.30".to_string()),
+                (Guild::Skyly, Area::Forest1, "0.20".to_string()),
+                (Guild::Oran, Area::Forest1, "0.06".to_string()),
+                (Guild::Oran, Area::Mines1, "0.04".to_string()),
+            ]
+        );
+
+        let oran = &plans[2];
+        assert_eq!(oran.difficulty, Difficulty::Hard);
+        assert_eq!(oran.episode, Episode::I);
+        assert_eq!(
+            oran.contributions[0].source,
+            DropSource::Enemy("Booma".to_string())
+        );
+        assert_eq!(oran.contributions[1].source, DropSource::Box);
+        assert_eq!(
+            oran.to_string(),
+            "Oran Hard Forest 1 (Episode I): 0.0600 expected per run\n  \
+             20 x Booma: Item A at 1/400 = 0.0500\n  \
+             10 x Boxes: Item A at 1/1000 = 0.0100"
+        );
+    }
+
+    #[test]
+    fn test_plan_hunt_several_items() {
+        let (enemies, boxes, spawns) = tables();
+        let plans = plan_hunt(&["Item A", "Item B"], &enemies, &boxes, &spawns);
+        // Oran Mines 1 gains 15 Dubchic at 1/10 for Item B
+        assert_eq!(plans[0].guild, Guild::Oran);
+        assert_eq!(plans[0].area, Area::Mines1);
+        assert!((plans[0].expected - 1.54).abs() < 1e-9);
+
+        assert!(plan_hunt(&["Item Z"], &enemies, &boxes, &spawns).is_empty());
+    }
+
+    #[test]
+    fn test_plan_hunt_with_set() {
+        let (enemies, boxes, spawns) = tables();
+        let mut weights = [1; 12];
+        weights[crate::WeaponType::Canes as usize] = 89;
+        let table = crate::DropTable {
+            guild: Guild::Skyly,
+            version: Some(GameVersion::V2),
+            difficulty: Some(Difficulty::Hard),
+            weights: crate::DropWeights::new(weights).unwrap(),
+        };
+        let set = DropTableSet::new(vec![table], true).unwrap();
+
+        let plans = set.plan_hunt(&["item a"], &enemies, &boxes, &spawns, GameVersion::V2);
+        assert_eq!(plans.len(), 4);
+        let weapons = plans[0].weapons.unwrap();
+        assert_eq!(weapons.difficulty, Some(Difficulty::Hard));
+        assert_eq!(weapons.common_drop.weapon, "Canes");
+        assert_eq!(
+            plans[0].to_string().lines().nth(1).unwrap(),
+            "  Weapons: Common: Canes (89%), Rare: Machineguns (1%)"
+        );
+
+        // Every hunt in the table is on Hard, which V1 has too
+        let v1 = set.plan_hunt(&["item a"], &enemies, &boxes, &spawns, GameVersion::V1);
+        assert_eq!(v1.len(), 4);
+        assert_ne!(v1[0].weapons, plans[0].weapons);
+    }
+}
@@ -0,0 +1,346 @@
+//! The game's item pattern tables (ItemPT.afs and ItemPT.gsl)
+//!
+//! Each Section ID, difficulty and episode has its own table deciding what
+//! non-rare items look like: which weapon types drop, how often weapons are
+//! ground or get a special, and how many slots armor has. V2 ships them as
+//! `ItemPT.afs` (Episode I only, ordered by difficulty then Section ID);
+//! GameCube and Blue Burst ship `ItemPT.gsl`, naming each table like
+//! `ItemPTn0.rel`.
+//!
+//! Only the fields below are decoded; the rest of each table is kept byte for
+//! byte, so a file that's read and written back is unchanged. The offsets are
+//! taken from the item pattern table struct in newserv's
+//! `src/CommonItemSet.hh` (`base_weapon_type_prob_table`, `grind_prob_table`,
+//! `armor_slot_count_prob_table`, `special_mult` and `special_percent`). They
+//! haven't been checked against a retail file here: the test fixtures are
+//! written by hand with made-up values at these offsets.
+//!
+//! | Offset  | Size   | Field                                          |
+//! |---------|--------|------------------------------------------------|
+//! | `0x000` | 12     | Weapon type weights, in [`WeaponType::ALL`] order |
+//! | `0x024` | 9 x 4  | Grind weights, per grind (+0 to +8) and area group |
+//! | `0x04D` | 5      | Armor slot count weights (0 to 4 slots)        |
+//! | `0x440` | 10     | Special multiplier, per area group             |
+//! | `0x44A` | 10     | Special percentage, per area group             |
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use psoid::{Difficulty, Episode, Guild, ItemPatternSet, WeaponType};
+//!
+//! let set = ItemPatternSet::from_gsl(&std::fs::read("ItemPT.gsl").unwrap()).unwrap();
+//! let table = set.get(Episode::I, Difficulty::Ultimate, Guild::Redria).unwrap();
+//! let rates = table.drop_rates().unwrap();
+//! println!("Redria drops partisans {}% of the time", rates[WeaponType::Partisans]);
+//! ```
+
+use crate::archive::{self, malformed, TableKey, Tables};
+use crate::{
+    Difficulty, DropRates, DropWeights, Episode, GameVersion, Guild, PsoidError, WeaponType,
+};
+
+const WEAPON_WEIGHTS: usize = 0x000;
+const GRIND_WEIGHTS: usize = 0x024;
+const SLOT_WEIGHTS: usize = 0x04D;
+const SPECIAL_MULT: usize = 0x440;
+const SPECIAL_PERCENT: usize = 0x44A;
+
+/// The name every table in an ItemPT archive starts with
+const PREFIX: &str = "ItemPT";
+
+/// One Section ID's item pattern table for one difficulty and episode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemPattern {
+    data: Vec<u8>,
+}
+
+impl ItemPattern {
+    /// The shortest table that holds every decoded field
+    pub const MIN_LEN: usize = SPECIAL_PERCENT + 10;
+
+    /// Wrap a table's raw bytes
+    pub fn from_bytes(data: &[u8]) -> Result<ItemPattern, PsoidError> {
+        if data.len() < Self::MIN_LEN {
+            return Err(malformed(format!(
+                "item pattern table is {} bytes, expected at least {}",
+                data.len(),
+                Self::MIN_LEN
+            )));
+        }
+        Ok(ItemPattern {
+            data: data.to_vec(),
+        })
+    }
+
+    /// Get the raw bytes, including the fields that aren't decoded
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the weapon type weights
+    ///
+    /// Fails if every weight is zero, as no weapon could ever drop.
+    pub fn drop_weights(&self) -> Result<DropWeights, PsoidError> {
+        DropWeights::new(self.array::<12>(WEAPON_WEIGHTS).map(u32::from))
+    }
+
+    /// Get the weapon type weights as whole percentages
+    pub fn drop_rates(&self) -> Result<DropRates, PsoidError> {
+        Ok(self.drop_weights()?.percentages())
+    }
+
+    /// Set the weight for a weapon type
+    pub fn set_weapon_weight(&mut self, weapon: WeaponType, weight: u8) {
+        self.data[WEAPON_WEIGHTS + weapon as usize] = weight;
+    }
+
+    /// Get the grind weights, indexed by grind then by area group
+    pub fn grind_weights(&self) -> [[u8; 4]; 9] {
+        std::array::from_fn(|grind| self.array(GRIND_WEIGHTS + grind * 4))
+    }
+
+    /// Get the armor slot count weights, from 0 to 4 slots
+    pub fn slot_weights(&self) -> [u8; 5] {
+        self.array(SLOT_WEIGHTS)
+    }
+
+    /// Get the special multiplier for each area group
+    pub fn special_mult(&self) -> [u8; 10] {
+        self.array(SPECIAL_MULT)
+    }
+
+    /// Get the chance, in percent, of a weapon having a special in each area group
+    pub fn special_percent(&self) -> [u8; 10] {
+        self.array(SPECIAL_PERCENT)
+    }
+
+    fn array<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data[offset..offset + N]
+            .try_into()
+            .expect("tables are at least MIN_LEN bytes")
+    }
+}
+
+/// An item pattern table and who it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemPatternEntry {
+    pub episode: Episode,
+    pub difficulty: Difficulty,
+    pub guild: Guild,
+    pub pattern: ItemPattern,
+}
+
+/// Every table in an ItemPT archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemPatternSet {
+    entries: Vec<ItemPatternEntry>,
+    version: GameVersion,
+}
+
+impl ItemPatternSet {
+    /// Read a V2 `ItemPT.afs`
+    pub fn from_afs(bytes: &[u8]) -> Result<ItemPatternSet, PsoidError> {
+        ItemPatternSet::from_tables(archive::read_afs_tables(bytes)?, GameVersion::V2)
+    }
+
+    /// Read a GameCube or Blue Burst `ItemPT.gsl`
+    ///
+    /// GameCube archives are big-endian and Blue Burst ones little-endian.
+    pub fn from_gsl(bytes: &[u8]) -> Result<ItemPatternSet, PsoidError> {
+        let (tables, big_endian) = archive::read_gsl_tables(PREFIX, bytes)?;
+        let version = if big_endian {
+            GameVersion::GameCube
+        } else {
+            GameVersion::BlueBurst
+        };
+        ItemPatternSet::from_tables(tables, version)
+    }
+
+    fn from_tables(tables: Tables, version: GameVersion) -> Result<ItemPatternSet, PsoidError> {
+        let entries = tables
+            .into_iter()
+            .map(|((episode, difficulty, guild), data)| {
+                Ok(ItemPatternEntry {
+                    episode,
+                    difficulty,
+                    guild,
+                    pattern: ItemPattern::from_bytes(&data)?,
+                })
+            })
+            .collect::<Result<_, PsoidError>>()?;
+        Ok(ItemPatternSet { entries, version })
+    }
+
+    /// Write the tables as an `ItemPT.afs`
+    ///
+    /// AFS files are found by position, so the tables must be Episode I and
+    /// in order from Normal Viridia with none missing.
+    pub fn to_afs(&self) -> Result<Vec<u8>, PsoidError> {
+        archive::write_afs_tables(&self.raw_tables())
+    }
+
+    /// Write the tables as an `ItemPT.gsl`, in the byte order it was read with
+    pub fn to_gsl(&self) -> Result<Vec<u8>, PsoidError> {
+        let big_endian = self.version == GameVersion::GameCube;
+        archive::write_gsl_tables(PREFIX, &self.raw_tables(), big_endian)
+    }
+
+    /// Get the version the archive is from, going by its format: V2 for an
+    /// AFS, GameCube for a big-endian GSL and Blue Burst for a little-endian
+    /// one
+    pub fn version(&self) -> GameVersion {
+        self.version
+    }
+
+    fn raw_tables(&self) -> Vec<(TableKey, &[u8])> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                (
+                    (entry.episode, entry.difficulty, entry.guild),
+                    entry.pattern.as_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    /// Get every table, in archive order
+    pub fn entries(&self) -> &[ItemPatternEntry] {
+        &self.entries
+    }
+
+    /// Get the table for a Section ID, if the archive has one
+    pub fn get(
+        &self,
+        episode: Episode,
+        difficulty: Difficulty,
+        guild: Guild,
+    ) -> Option<&ItemPattern> {
+        self.position(episode, difficulty, guild)
+            .map(|index| &self.entries[index].pattern)
+    }
+
+    /// Get the table for a Section ID to edit it
+    pub fn get_mut(
+        &mut self,
+        episode: Episode,
+        difficulty: Difficulty,
+        guild: Guild,
+    ) -> Option<&mut ItemPattern> {
+        self.position(episode, difficulty, guild)
+            .map(|index| &mut self.entries[index].pattern)
+    }
+
+    fn position(&self, episode: Episode, difficulty: Difficulty, guild: Guild) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry.episode == episode && entry.difficulty == difficulty && entry.guild == guild
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFS_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/ItemPT.afs");
+    const GSL_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/ItemPT.gsl");
+
+    /// Get the raw weapon weights of a table, in [`WeaponType::ALL`] order
+    fn weights(pattern: &ItemPattern) -> [u32; 12] {
+        let weights = pattern.drop_weights().unwrap();
+        WeaponType::ALL.map(|weapon| weights.weight(weapon))
+    }
+
+    #[test]
+    fn test_afs_fixture_round_trip() {
+        let set = ItemPatternSet::from_afs(AFS_FIXTURE).unwrap();
+        assert_eq!(set.entries().len(), 2);
+        assert_eq!(set.version(), GameVersion::V2);
+        assert_eq!(set.to_afs().unwrap(), AFS_FIXTURE);
+
+        let viridia = set
+            .get(Episode::I, Difficulty::Normal, Guild::Viridia)
+            .unwrap();
+        assert_eq!(
+            weights(viridia),
+            [24, 9, 14, 18, 3, 22, 11, 9, 16, 20, 8, 6]
+        );
+        assert_eq!(viridia.drop_weights().unwrap().total(), 160);
+        // 24 of 160 is 15%
+        assert_eq!(viridia.drop_rates().unwrap().sabers, 15);
+        assert_eq!(viridia.slot_weights(), [40, 30, 20, 8, 2]);
+        assert_eq!(viridia.grind_weights()[0], [60, 50, 40, 30]);
+        assert_eq!(viridia.special_percent()[9], 19);
+
+        let greenill = set
+            .get(Episode::I, Difficulty::Normal, Guild::Greennill)
+            .unwrap();
+        assert_eq!(
+            weights(greenill),
+            [18, 2, 15, 10, 9, 22, 25, 12, 6, 20, 11, 10]
+        );
+        assert_eq!(set.get(Episode::I, Difficulty::Hard, Guild::Viridia), None);
+    }
+
+    #[test]
+    fn test_gsl_fixture_round_trip() {
+        let set = ItemPatternSet::from_gsl(GSL_FIXTURE).unwrap();
+        assert_eq!(set.version(), GameVersion::GameCube);
+        assert_eq!(set.to_gsl().unwrap(), GSL_FIXTURE);
+
+        let entry = &set.entries()[0];
+        assert_eq!(
+            (entry.episode, entry.difficulty, entry.guild),
+            (Episode::I, Difficulty::Normal, Guild::Viridia)
+        );
+        assert_eq!(
+            weights(&entry.pattern),
+            [30, 12, 10, 14, 2, 25, 10, 8, 15, 18, 9, 7]
+        );
+
+        let entry = &set.entries()[1];
+        assert_eq!(
+            (entry.episode, entry.difficulty, entry.guild),
+            (Episode::II, Difficulty::Ultimate, Guild::Redria)
+        );
+        assert_eq!(
+            weights(&entry.pattern),
+            [26, 14, 1, 12, 40, 20, 9, 10, 12, 18, 8, 10]
+        );
+        assert_eq!(entry.pattern.drop_weights().unwrap().total(), 180);
+        assert_eq!(entry.pattern.special_mult(), [1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        // The AFS only allows Episode I tables
+        assert!(set.to_afs().is_err());
+    }
+
+    #[test]
+    fn test_edit_table() {
+        let mut set = ItemPatternSet::from_gsl(GSL_FIXTURE).unwrap();
+        let table = set
+            .get_mut(Episode::I, Difficulty::Normal, Guild::Viridia)
+            .unwrap();
+        for weapon in WeaponType::ALL {
+            table.set_weapon_weight(weapon, 0);
+        }
+        assert_eq!(
+            table.drop_rates().unwrap_err(),
+            PsoidError::InvalidDropTable("every weight is zero".to_string())
+        );
+        table.set_weapon_weight(WeaponType::Rods, 3);
+        assert_eq!(table.drop_rates().unwrap().rods, 100);
+
+        let written = set.to_gsl().unwrap();
+        assert_ne!(written, GSL_FIXTURE);
+        assert_eq!(ItemPatternSet::from_gsl(&written).unwrap(), set);
+    }
+
+    #[test]
+    fn test_short_table() {
+        assert_eq!(
+            ItemPattern::from_bytes(&[0; 16]).unwrap_err(),
+            PsoidError::MalformedFile(
+                "item pattern table is 16 bytes, expected at least 1108".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,596 @@
+//! The game's rare item tables (ItemRT.afs and ItemRT.gsl)
+//!
+//! Each Section ID, difficulty and episode has a 0x280-byte table giving the
+//! rare item for every enemy and a set of box rares. The archives are laid
+//! out like the item pattern tables: `ItemRT.afs` on V2, `ItemRT.gsl` (with
+//! tables named like `ItemRTn0.rel`) on GameCube and Blue Burst.
+//!
+//! | Offset  | Size     | Field                                           |
+//! |---------|----------|-------------------------------------------------|
+//! | `0x000` | 101 x 4  | Enemy rares, by enemy index: rate byte, item code |
+//! | `0x194` | 30       | Area number of each box rare                    |
+//! | `0x1B2` | 30 x 4   | Box rares: rate byte, item code                 |
+//!
+//! The rest of the table is kept byte for byte. Item codes are the game's
+//! three-byte codes (`000100` is a Saber) and area numbers are the game's
+//! floor numbers; the crate doesn't name either. To query a set like a
+//! [`RareTable`](crate::RareTable), name the items and enemies you care about
+//! in [`RareItemNames`] and convert it with
+//! [`RareTable::from_item_rt`](crate::RareTable::from_item_rt).
+//!
+//! A rate byte packs a probability out of 2^32: the top five bits are an
+//! exponent and the low three bits a mantissa, see [`decode_rare_rate`].
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{decode_rare_rate, encode_rare_rate};
+//!
+//! let rate = decode_rare_rate(0x8D);
+//! assert_eq!(rate.to_string(), "3/65536");
+//! assert_eq!(encode_rare_rate(rate), Some(0x8D));
+//! ```
+
+use crate::archive::{self, malformed, TableKey, Tables};
+use crate::rares::read_csv;
+use crate::{Difficulty, Episode, Guild, Probability, PsoidError};
+use std::fmt;
+
+const ENEMY_RARES: usize = 0x000;
+const BOX_AREAS: usize = 0x194;
+const BOX_RARES: usize = 0x1B2;
+const ENEMY_COUNT: usize = 0x65;
+const BOX_COUNT: usize = 0x1E;
+
+/// The name every table in an ItemRT archive starts with
+const PREFIX: &str = "ItemRT";
+
+/// Expand a rate byte into the exact drop probability
+///
+/// The game computes `(2 << shift) * ((byte & 7) + 7)` out of 2^32, where
+/// `shift` is the top five bits minus 4 (but at least 0).
+pub fn decode_rare_rate(byte: u8) -> Probability {
+    let shift = (u32::from(byte >> 3)).saturating_sub(4);
+    let rate = (2u64 << shift) * (u64::from(byte & 7) + 7);
+    Probability::from_u64(rate, 1 << 32).expect("every rate byte is below 2^32")
+}
+
+/// Find the rate byte for a probability
+///
+/// Returns `None` if no byte decodes to exactly this probability. Some rates
+/// have more than one byte (`0x07`, `0x27` and `0x28` are all 28 out of
+/// 2^32); the lowest is returned.
+pub fn encode_rare_rate(probability: Probability) -> Option<u8> {
+    (0..=u8::MAX).find(|&byte| decode_rare_rate(byte) == probability)
+}
+
+/// One rare drop slot: an encoded rate and a three-byte item code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RareSlot {
+    pub rate: u8,
+    pub item: [u8; 3],
+}
+
+impl RareSlot {
+    /// Get the exact chance of the item dropping
+    pub fn probability(&self) -> Probability {
+        decode_rare_rate(self.rate)
+    }
+
+    /// Whether the slot is unused (all zeros)
+    pub fn is_empty(&self) -> bool {
+        *self == RareSlot::default()
+    }
+
+    fn read(bytes: &[u8]) -> RareSlot {
+        RareSlot {
+            rate: bytes[0],
+            item: [bytes[1], bytes[2], bytes[3]],
+        }
+    }
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes[0] = self.rate;
+        bytes[1..4].copy_from_slice(&self.item);
+    }
+}
+
+/// Show the item code and rate, e.g. "000100 at 3/65536 (0.0046%)", or
+/// "nothing" for an empty slot
+impl fmt::Display for RareSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("nothing");
+        }
+        let probability = self.probability();
+        write!(
+            f,
+            "{:02X}{:02X}{:02X} at {} ({:.4}%)",
+            self.item[0],
+            self.item[1],
+            self.item[2],
+            probability,
+            probability.percent()
+        )
+    }
+}
+
+/// One Section ID's rare item table for one difficulty and episode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RareItemTable {
+    /// Rares by enemy index
+    pub enemies: [RareSlot; ENEMY_COUNT],
+    /// The area number each box rare drops in
+    pub box_areas: [u8; BOX_COUNT],
+    pub boxes: [RareSlot; BOX_COUNT],
+    /// Whatever follows the box rares, kept as is
+    rest: Vec<u8>,
+}
+
+impl RareItemTable {
+    /// The shortest table that holds every decoded field
+    pub const MIN_LEN: usize = BOX_RARES + BOX_COUNT * 4;
+
+    /// Decode a table's raw bytes
+    pub fn from_bytes(data: &[u8]) -> Result<RareItemTable, PsoidError> {
+        if data.len() < Self::MIN_LEN {
+            return Err(malformed(format!(
+                "rare item table is {} bytes, expected at least {}",
+                data.len(),
+                Self::MIN_LEN
+            )));
+        }
+        Ok(RareItemTable {
+            enemies: std::array::from_fn(|index| RareSlot::read(&data[ENEMY_RARES + index * 4..])),
+            box_areas: std::array::from_fn(|index| data[BOX_AREAS + index]),
+            boxes: std::array::from_fn(|index| RareSlot::read(&data[BOX_RARES + index * 4..])),
+            rest: data[Self::MIN_LEN..].to_vec(),
+        })
+    }
+
+    /// Encode the table, keeping any bytes that aren't decoded
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0; Self::MIN_LEN];
+        for (index, slot) in self.enemies.iter().enumerate() {
+            slot.write(&mut data[ENEMY_RARES + index * 4..]);
+        }
+        data[BOX_AREAS..BOX_AREAS + BOX_COUNT].copy_from_slice(&self.box_areas);
+        for (index, slot) in self.boxes.iter().enumerate() {
+            slot.write(&mut data[BOX_RARES + index * 4..]);
+        }
+        data.extend_from_slice(&self.rest);
+        data
+    }
+
+    /// Get the enemies that have a rare, with their index
+    pub fn enemy_rares(&self) -> Vec<(usize, &RareSlot)> {
+        self.enemies
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| !slot.is_empty())
+            .collect()
+    }
+
+    /// Get the box rares in use, with the area number they drop in
+    pub fn box_rares(&self) -> Vec<(u8, &RareSlot)> {
+        self.box_areas
+            .iter()
+            .zip(&self.boxes)
+            .filter(|(_, slot)| !slot.is_empty())
+            .map(|(&area, slot)| (area, slot))
+            .collect()
+    }
+}
+
+/// A rare item table and who it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RareItemEntry {
+    pub episode: Episode,
+    pub difficulty: Difficulty,
+    pub guild: Guild,
+    pub table: RareItemTable,
+}
+
+/// Where a rare slot is in a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RareSource {
+    /// An enemy, by index
+    Enemy(usize),
+    /// A box rare, by index, with the area number in the newer table
+    Box { index: usize, area: u8 },
+}
+
+/// A rare slot that differs between two sets of tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RareChange {
+    pub episode: Episode,
+    pub difficulty: Difficulty,
+    pub guild: Guild,
+    pub source: RareSource,
+    pub before: RareSlot,
+    pub after: RareSlot,
+}
+
+impl fmt::Display for RareChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            self.episode,
+            self.difficulty,
+            self.guild.name()
+        )?;
+        match self.source {
+            RareSource::Enemy(index) => write!(f, "enemy {}", index)?,
+            RareSource::Box { index, area } => write!(f, "box {} (area {})", index, area)?,
+        }
+        write!(f, ": {} -> {}", self.before, self.after)
+    }
+}
+
+/// Every table in an ItemRT archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RareItemSet {
+    entries: Vec<RareItemEntry>,
+    big_endian: bool,
+}
+
+impl RareItemSet {
+    /// Read a V2 `ItemRT.afs`
+    pub fn from_afs(bytes: &[u8]) -> Result<RareItemSet, PsoidError> {
+        RareItemSet::from_tables(archive::read_afs_tables(bytes)?, false)
+    }
+
+    /// Read a GameCube or Blue Burst `ItemRT.gsl`
+    pub fn from_gsl(bytes: &[u8]) -> Result<RareItemSet, PsoidError> {
+        let (tables, big_endian) = archive::read_gsl_tables(PREFIX, bytes)?;
+        RareItemSet::from_tables(tables, big_endian)
+    }
+
+    fn from_tables(tables: Tables, big_endian: bool) -> Result<RareItemSet, PsoidError> {
+        let entries = tables
+            .into_iter()
+            .map(|((episode, difficulty, guild), data)| {
+                Ok(RareItemEntry {
+                    episode,
+                    difficulty,
+                    guild,
+                    table: RareItemTable::from_bytes(&data)?,
+                })
+            })
+            .collect::<Result<_, PsoidError>>()?;
+        Ok(RareItemSet {
+            entries,
+            big_endian,
+        })
+    }
+
+    /// Write the tables as an `ItemRT.afs`
+    ///
+    /// AFS files are found by position, so the tables must be Episode I and
+    /// in order from Normal Viridia with none missing.
+    pub fn to_afs(&self) -> Result<Vec<u8>, PsoidError> {
+        let data = self.raw_tables();
+        archive::write_afs_tables(&borrow(&data))
+    }
+
+    /// Write the tables as an `ItemRT.gsl`, in the byte order it was read with
+    pub fn to_gsl(&self) -> Result<Vec<u8>, PsoidError> {
+        let data = self.raw_tables();
+        archive::write_gsl_tables(PREFIX, &borrow(&data), self.big_endian)
+    }
+
+    fn raw_tables(&self) -> Tables {
+        self.entries
+            .iter()
+            .map(|entry| {
+                (
+                    (entry.episode, entry.difficulty, entry.guild),
+                    entry.table.to_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    /// Get every table, in archive order
+    pub fn entries(&self) -> &[RareItemEntry] {
+        &self.entries
+    }
+
+    /// Get the table for a Section ID, if the archive has one
+    pub fn get(
+        &self,
+        episode: Episode,
+        difficulty: Difficulty,
+        guild: Guild,
+    ) -> Option<&RareItemTable> {
+        self.position(episode, difficulty, guild)
+            .map(|index| &self.entries[index].table)
+    }
+
+    /// Get the table for a Section ID to edit it
+    pub fn get_mut(
+        &mut self,
+        episode: Episode,
+        difficulty: Difficulty,
+        guild: Guild,
+    ) -> Option<&mut RareItemTable> {
+        self.position(episode, difficulty, guild)
+            .map(|index| &mut self.entries[index].table)
+    }
+
+    fn position(&self, episode: Episode, difficulty: Difficulty, guild: Guild) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry.episode == episode && entry.difficulty == difficulty && entry.guild == guild
+        })
+    }
+
+    /// List every slot that differs in `other`, such as a server's edited
+    /// tables compared with retail ones
+    ///
+    /// A table only one set has is compared with an empty table. A box rare
+    /// also counts as changed if only its area moved.
+    pub fn diff(&self, other: &RareItemSet) -> Vec<RareChange> {
+        let empty = RareItemTable::from_bytes(&[0; RareItemTable::MIN_LEN])
+            .expect("an empty table is long enough");
+        let mut keys: Vec<TableKey> = Vec::new();
+        for entry in self.entries.iter().chain(&other.entries) {
+            let key = (entry.episode, entry.difficulty, entry.guild);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (episode, difficulty, guild) in keys {
+            let before = self.get(episode, difficulty, guild).unwrap_or(&empty);
+            let after = other.get(episode, difficulty, guild).unwrap_or(&empty);
+            let change = |source, before: &RareSlot, after: &RareSlot| RareChange {
+                episode,
+                difficulty,
+                guild,
+                source,
+                before: *before,
+                after: *after,
+            };
+
+            for (index, (old, new)) in before.enemies.iter().zip(&after.enemies).enumerate() {
+                if old != new {
+                    changes.push(change(RareSource::Enemy(index), old, new));
+                }
+            }
+            for index in 0..BOX_COUNT {
+                let (old, new) = (&before.boxes[index], &after.boxes[index]);
+                let area = after.box_areas[index];
+                if old != new || before.box_areas[index] != area {
+                    changes.push(change(RareSource::Box { index, area }, old, new));
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Names for the item codes and enemy indexes in rare item tables
+///
+/// None are bundled. Codes and indexes without a name are shown as the code
+/// in hex ("000100") or as "enemy 1".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RareItemNames {
+    items: Vec<([u8; 3], String)>,
+    enemies: Vec<(usize, String)>,
+}
+
+impl RareItemNames {
+    /// Columns required in a names CSV header
+    ///
+    /// `kind` is "item" or "enemy". An item's `id` is its code as six hex
+    /// digits and an enemy's is its index in the table.
+    pub const COLUMNS: [&'static str; 3] = ["kind", "id", "name"];
+
+    /// Name an item code, replacing any earlier name
+    pub fn name_item(&mut self, code: [u8; 3], name: impl Into<String>) {
+        self.items.retain(|(item, _)| *item != code);
+        self.items.push((code, name.into()));
+    }
+
+    /// Name an enemy index, replacing any earlier name
+    pub fn name_enemy(&mut self, index: usize, name: impl Into<String>) {
+        self.enemies.retain(|(enemy, _)| *enemy != index);
+        self.enemies.push((index, name.into()));
+    }
+
+    /// Read names from CSV with a header row
+    ///
+    /// Column order doesn't matter and extra columns are ignored. Any bad row
+    /// fails the whole file, naming the line.
+    pub fn from_csv(input: &str) -> Result<RareItemNames, PsoidError> {
+        let mut names = RareItemNames::default();
+        let rows = read_csv(input, Self::COLUMNS, |row| {
+            let id = row.get(1)?;
+            let kind = row.get(0)?.to_lowercase();
+            let name = row.get(2)?.to_string();
+            match kind.as_str() {
+                "item" => {
+                    let code = parse_item_code(id).ok_or_else(|| {
+                        row.error(format!("item id must be six hex digits, got {}", id))
+                    })?;
+                    Ok((Ok(code), name))
+                }
+                "enemy" => match row.number(1)? as usize {
+                    index if index < ENEMY_COUNT => Ok((Err(index), name)),
+                    index => Err(row.error(format!(
+                        "enemy index must be below {}, got {}",
+                        ENEMY_COUNT, index
+                    ))),
+                },
+                _ => Err(row.error(format!("kind must be item or enemy, got {}", kind))),
+            }
+        })?;
+        for (id, name) in rows {
+            match id {
+                Ok(code) => names.name_item(code, name),
+                Err(index) => names.name_enemy(index, name),
+            }
+        }
+        Ok(names)
+    }
+
+    /// Get the name of an item code, or the code in hex
+    pub fn item(&self, code: [u8; 3]) -> String {
+        match self.items.iter().find(|(item, _)| *item == code) {
+            Some((_, name)) => name.clone(),
+            None => format!("{:02X}{:02X}{:02X}", code[0], code[1], code[2]),
+        }
+    }
+
+    /// Get the name of an enemy index, or "enemy" and the index
+    pub fn enemy(&self, index: usize) -> String {
+        match self.enemies.iter().find(|(enemy, _)| *enemy == index) {
+            Some((_, name)) => name.clone(),
+            None => format!("enemy {}", index),
+        }
+    }
+}
+
+/// Parse six hex digits into an item code
+fn parse_item_code(value: &str) -> Option<[u8; 3]> {
+    if value.len() != 6 || !value.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |start: usize| u8::from_str_radix(&value[start..start + 2], 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?])
+}
+
+fn borrow(tables: &Tables) -> Vec<(TableKey, &[u8])> {
+    tables
+        .iter()
+        .map(|(key, data)| (*key, data.as_slice()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFS_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/ItemRT.afs");
+    const GSL_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/ItemRT.gsl");
+
+    #[test]
+    fn test_decode_rare_rate() {
+        // The smallest bytes all share the lowest exponent
+        assert_eq!(decode_rare_rate(0x00).to_string(), "7/2147483648");
+        assert_eq!(decode_rare_rate(0x20), decode_rare_rate(0x00));
+        // 0x8D: shift 13, mantissa 5, so (2 << 13) * 12 = 3 * 2^16 out of 2^32
+        assert_eq!(decode_rare_rate(0x8D), Probability::new(3, 65536).unwrap());
+        assert_eq!(decode_rare_rate(0xFF), Probability::new(7, 8).unwrap());
+        // A full mantissa equals the next exponent's empty one
+        assert_eq!(decode_rare_rate(0x27), decode_rare_rate(0x28));
+        assert_eq!(encode_rare_rate(decode_rare_rate(0x28)), Some(0x07));
+
+        for byte in 0..=u8::MAX {
+            let rate = decode_rare_rate(byte);
+            assert_eq!(decode_rare_rate(encode_rare_rate(rate).unwrap()), rate);
+        }
+        assert_eq!(encode_rare_rate(Probability::new(1, 3).unwrap()), None);
+    }
+
+    #[test]
+    fn test_afs_fixture_round_trip() {
+        let set = RareItemSet::from_afs(AFS_FIXTURE).unwrap();
+        assert_eq!(set.to_afs().unwrap(), AFS_FIXTURE);
+
+        let table = set
+            .get(Episode::I, Difficulty::Normal, Guild::Viridia)
+            .unwrap();
+        let rares = table.enemy_rares();
+        assert_eq!(rares.len(), 2);
+        assert_eq!(rares[0].0, 1);
+        assert_eq!(rares[0].1.item, [0x00, 0x01, 0x00]);
+        assert_eq!(rares[0].1.to_string(), "000100 at 3/65536 (0.0046%)");
+        assert_eq!(table.box_rares()[0].0, 3);
+    }
+
+    #[test]
+    fn test_gsl_fixture_round_trip() {
+        let set = RareItemSet::from_gsl(GSL_FIXTURE).unwrap();
+        assert_eq!(set.to_gsl().unwrap(), GSL_FIXTURE);
+        assert_eq!(set.entries().len(), 2);
+
+        let entry = &set.entries()[1];
+        assert_eq!(
+            (entry.episode, entry.difficulty, entry.guild),
+            (Episode::IV, Difficulty::Ultimate, Guild::Whitill)
+        );
+        assert_eq!(entry.table.to_bytes().len(), 0x280);
+        assert_eq!(entry.table.enemy_rares()[0].1.rate, 0xFF);
+    }
+
+    #[test]
+    fn test_edit_and_diff() {
+        let retail = RareItemSet::from_gsl(GSL_FIXTURE).unwrap();
+        assert!(retail.diff(&retail).is_empty());
+        assert_eq!(RareSlot::default().to_string(), "nothing");
+
+        let mut custom = retail.clone();
+        let table = custom
+            .get_mut(Episode::I, Difficulty::Normal, Guild::Viridia)
+            .unwrap();
+        table.enemies[1].rate = encode_rare_rate(Probability::new(7, 8).unwrap()).unwrap();
+        table.box_areas[0] = 4;
+
+        let custom = RareItemSet::from_gsl(&custom.to_gsl().unwrap()).unwrap();
+        let changes = retail.diff(&custom);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].source, RareSource::Enemy(1));
+        assert_eq!(
+            changes[0].after.probability(),
+            Probability::new(7, 8).unwrap()
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "Episode I Normal Viridia box 0 (area 4): \
+             000200 at 1/1024 (0.0977%) -> 000200 at 1/1024 (0.0977%)"
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        let names = RareItemNames::from_csv(
+            "kind,id,name\n\
+             item,000100,Saber\n\
+             enemy,1,Hildebear\n\
+             item,000100,Saber (renamed)\n",
+        )
+        .unwrap();
+        assert_eq!(names.item([0x00, 0x01, 0x00]), "Saber (renamed)");
+        assert_eq!(names.item([0x00, 0x02, 0x00]), "000200");
+        assert_eq!(names.enemy(1), "Hildebear");
+        assert_eq!(names.enemy(2), "enemy 2");
+
+        assert_eq!(
+            RareItemNames::from_csv("kind,id,name\nitem,0001,Saber\n").unwrap_err(),
+            PsoidError::MalformedRow(
+                "line 2: item id must be six hex digits, got 0001".to_string()
+            )
+        );
+        assert_eq!(
+            RareItemNames::from_csv("kind,id,name\nenemy,101,Boss\n").unwrap_err(),
+            PsoidError::MalformedRow("line 2: enemy index must be below 101, got 101".to_string())
+        );
+        assert_eq!(
+            RareItemNames::from_csv("kind,id,name\nbox,1,X\n").unwrap_err(),
+            PsoidError::MalformedRow("line 2: kind must be item or enemy, got box".to_string())
+        );
+    }
+
+    #[test]
+    fn test_short_table() {
+        assert_eq!(
+            RareItemTable::from_bytes(&[0; 0x100]).unwrap_err(),
+            PsoidError::MalformedFile(
+                "rare item table is 256 bytes, expected at least 554".to_string()
+            )
+        );
+    }
+}
@@ -18,16 +18,230 @@
 //! assert_eq!(guild.name(), "Bluefull");
 //! ```
 
+use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
+
+mod algorithm;
+mod archive;
+mod area;
+mod class_picker;
+mod compare;
+mod difficulty;
+mod drop_tables;
+mod episode;
+mod guild_info;
+mod hunt;
+mod item_pt;
+mod item_rt;
+mod probability;
+mod rares;
+mod recommend;
+mod region;
+mod report;
+mod roster;
+mod search;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod suggest;
+mod weapon;
+
+pub use algorithm::{
+    Algorithms, AsciiAlgorithm, BlueBurstAlgorithm, SectionIdAlgorithm, ShiftJisAlgorithm,
+};
+pub use area::Area;
+pub use class_picker::{classes_for_guild, guilds_with_append, AppendOption};
+pub use compare::{compare, compare_with, Comparison, ComparisonEntry};
+pub use difficulty::Difficulty;
+pub use drop_tables::{DropTable, DropTableSet};
+pub use episode::Episode;
+pub use guild_info::GuildInfo;
+pub use hunt::{plan_hunt, Contribution, DropSource, HuntPlan, SpawnTable};
+pub use item_pt::{ItemPattern, ItemPatternEntry, ItemPatternSet};
+pub use item_rt::{
+    decode_rare_rate, encode_rare_rate, RareChange, RareItemEntry, RareItemNames, RareItemSet,
+    RareItemTable, RareSlot, RareSource,
+};
+pub use probability::{DropWeights, Probability};
+pub use rares::{BoxRare, BoxRareTable, RareDrop, RareTable};
+pub use recommend::{rank_guilds, GuildScore, WeaponScore};
+pub use region::Region;
+pub use report::{render_names, DropSummary, GuildReport, NameReport, OutputFormat};
+pub use roster::{process_roster, Roster, RosterError, RosterFormat};
+pub use search::{find_names, find_names_with, NameSearch, SearchOptions};
+#[cfg(feature = "serde")]
+pub use serde_impls::guild_id;
+pub use suggest::{suggest_edits, suggest_edits_with, Edit, Suggestion};
+pub use weapon::{WeaponDrop, WeaponType};
+
+/// Maximum number of characters allowed in a character name
+pub const MAX_NAME_LEN: usize = 12;
+
+/// Errors that can occur while calculating a Section ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PsoidError {
+    /// The name was empty
+    EmptyName,
+    /// The name was longer than the game allows (in Shift-JIS bytes for the
+    /// Japanese V1, V2 and GameCube releases)
+    NameTooLong { len: usize, max: usize },
+    /// A V1/V2 name contained a non-ASCII character at the given character index
+    NonAscii { index: usize, ch: char },
+    /// A Blue Burst or Japanese name contained a character the game does not accept
+    UnsupportedCharacter { index: usize, ch: char },
+    /// A name's character values added up to more than a `u32` can hold
+    SumOverflow,
+    /// The text did not name a known game version
+    UnknownVersion(String),
+    /// The text did not name a known character class
+    UnknownClass(String),
+    /// The text did not name a known guild
+    UnknownGuild(String),
+    /// The text did not name a known weapon type
+    UnknownWeapon(String),
+    /// The text did not name a known difficulty
+    UnknownDifficulty(String),
+    /// The text did not name a known episode
+    UnknownEpisode(String),
+    /// The text did not name a known area
+    UnknownArea(String),
+    /// The text did not name a known region
+    UnknownRegion(String),
+    /// The text was not a drop rate (a fraction or a percentage)
+    InvalidRate(String),
+    /// The difficulty can't be played in the game version
+    UnavailableDifficulty {
+        version: GameVersion,
+        difficulty: Difficulty,
+    },
+    /// The number was not a guild ID (0-9)
+    InvalidGuildId(u32),
+    /// A roster row could not be read
+    MalformedRow(String),
+    /// A roster header did not have a required column
+    MissingColumn(&'static str),
+    /// A drop table's weights could not be used
+    InvalidDropTable(String),
+    /// A binary game file could not be read
+    MalformedFile(String),
+    /// A drop table set that doesn't inherit the built-in tables had no
+    /// table for a guild
+    MissingGuild(Guild),
+    /// A drop table had no weight for a weapon type
+    MissingWeapon { guild: Guild, weapon: WeaponType },
+}
+
+impl fmt::Display for PsoidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PsoidError::EmptyName => write!(f, "Name cannot be empty"),
+            PsoidError::NameTooLong { max, .. } => {
+                write!(f, "Name must be at most {} characters long", max)
+            }
+            PsoidError::NonAscii { .. } => write!(f, "Name must contain only ASCII characters"),
+            PsoidError::UnsupportedCharacter { index, ch } => {
+                write!(f, "Unsupported character: {} (at position {})", ch, index)
+            }
+            PsoidError::SumOverflow => write!(f, "Name's character values overflowed the sum"),
+            PsoidError::UnknownVersion(value) => write!(f, "Unknown version: {}", value),
+            PsoidError::UnknownClass(value) => write!(f, "Unknown class: {}", value),
+            PsoidError::UnknownGuild(value) => write!(f, "Unknown guild: {}", value),
+            PsoidError::UnknownWeapon(value) => write!(f, "Unknown weapon: {}", value),
+            PsoidError::UnknownDifficulty(value) => write!(f, "Unknown difficulty: {}", value),
+            PsoidError::UnknownEpisode(value) => write!(f, "Unknown episode: {}", value),
+            PsoidError::UnknownArea(value) => write!(f, "Unknown area: {}", value),
+            PsoidError::UnknownRegion(value) => write!(f, "Unknown region: {}", value),
+            PsoidError::InvalidRate(value) => write!(f, "Invalid drop rate: {}", value),
+            PsoidError::UnavailableDifficulty {
+                version,
+                difficulty,
+            } => write!(f, "{} is not available in {}", difficulty, version),
+            PsoidError::InvalidGuildId(id) => write!(f, "Invalid guild ID: {} (must be 0-9)", id),
+            PsoidError::MalformedRow(message) => write!(f, "Malformed row: {}", message),
+            PsoidError::MissingColumn(column) => write!(f, "Missing column: {}", column),
+            PsoidError::InvalidDropTable(message) => write!(f, "Invalid drop table: {}", message),
+            PsoidError::MalformedFile(message) => write!(f, "Malformed file: {}", message),
+            PsoidError::MissingGuild(guild) => {
+                write!(f, "Drop table set has no table for {}", guild.name())
+            }
+            PsoidError::MissingWeapon { guild, weapon } => {
+                write!(
+                    f,
+                    "Drop table for {} has no weight for {}",
+                    guild.name(),
+                    weapon
+                )
+            }
+        }
+    }
+}
+
+impl Error for PsoidError {}
 
 /// Represents the game version which affects Section ID calculation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameVersion {
     V1,
     V2,
+    /// Episode I & II on GameCube and Xbox, which uses the V2 calculation
+    GameCube,
     BlueBurst,
 }
 
+impl GameVersion {
+    /// Every game version, in release order
+    pub const ALL: [GameVersion; 4] = [
+        GameVersion::V1,
+        GameVersion::V2,
+        GameVersion::GameCube,
+        GameVersion::BlueBurst,
+    ];
+
+    /// Get the name of the version as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameVersion::V1 => "V1",
+            GameVersion::V2 => "V2",
+            GameVersion::GameCube => "GameCube",
+            GameVersion::BlueBurst => "BlueBurst",
+        }
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a version name, ignoring case, spaces, dashes and underscores
+///
+/// Accepts "v1"/"1", "v2"/"2", "ep1&2"/"gc"/"gamecube" and
+/// "bb"/"blue burst"/"BlueBurst".
+impl FromStr for GameVersion {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match normalize(value).as_str() {
+            "v1" | "1" | "ver1" | "version1" => Ok(GameVersion::V1),
+            "v2" | "2" | "ver2" | "version2" => Ok(GameVersion::V2),
+            "ep1&2" | "ep12" | "episode1&2" | "gc" | "gamecube" => Ok(GameVersion::GameCube),
+            "bb" | "blueburst" | "psobb" => Ok(GameVersion::BlueBurst),
+            _ => Err(PsoidError::UnknownVersion(value.to_string())),
+        }
+    }
+}
+
+/// Lowercase `value` and drop the separators players put in names
+fn normalize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '-' | '_' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Character class for BlueBurst version (affects Section ID calculation)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CharacterClass {
@@ -46,7 +260,42 @@ pub enum CharacterClass {
 }
 
 impl CharacterClass {
-    fn blueburst_offset(&self) -> u32 {
+    /// Every character class
+    pub const ALL: [CharacterClass; 12] = [
+        CharacterClass::HUmar,
+        CharacterClass::HUnewearl,
+        CharacterClass::HUcast,
+        CharacterClass::HUcaseal,
+        CharacterClass::RAmar,
+        CharacterClass::RAmarl,
+        CharacterClass::RAcast,
+        CharacterClass::RAcaseal,
+        CharacterClass::FOmar,
+        CharacterClass::FOmarl,
+        CharacterClass::FOnewm,
+        CharacterClass::FOnewearl,
+    ];
+
+    /// Get the name of the class as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharacterClass::HUmar => "HUmar",
+            CharacterClass::HUnewearl => "HUnewearl",
+            CharacterClass::HUcast => "HUcast",
+            CharacterClass::HUcaseal => "HUcaseal",
+            CharacterClass::RAmar => "RAmar",
+            CharacterClass::RAmarl => "RAmarl",
+            CharacterClass::RAcast => "RAcast",
+            CharacterClass::RAcaseal => "RAcaseal",
+            CharacterClass::FOmar => "FOmar",
+            CharacterClass::FOmarl => "FOmarl",
+            CharacterClass::FOnewm => "FOnewm",
+            CharacterClass::FOnewearl => "FOnewearl",
+        }
+    }
+
+    /// Get the amount this class adds to the Blue Burst name sum
+    pub fn blueburst_offset(&self) -> u32 {
         match self {
             CharacterClass::HUmar => 5,
             CharacterClass::HUnewearl => 6,
@@ -64,6 +313,25 @@ impl CharacterClass {
     }
 }
 
+impl fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a class name, ignoring case ("humar", "HUmar" and "HUMAR" all work)
+impl FromStr for CharacterClass {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        CharacterClass::ALL
+            .into_iter()
+            .find(|class| class.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| PsoidError::UnknownClass(value.to_string()))
+    }
+}
+
 /// Represents a guild in Phantasy Star Online
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Guild {
@@ -80,6 +348,37 @@ pub enum Guild {
 }
 
 impl Guild {
+    /// Every guild, in ID order
+    pub const ALL: [Guild; 10] = [
+        Guild::Viridia,
+        Guild::Greennill,
+        Guild::Skyly,
+        Guild::Bluefull,
+        Guild::Purplenum,
+        Guild::Pinkal,
+        Guild::Redria,
+        Guild::Oran,
+        Guild::Yellowboze,
+        Guild::Whitill,
+    ];
+
+    /// Get the guild for a numeric ID (0-9)
+    fn from_id(id: u32) -> Guild {
+        match id {
+            0 => Guild::Viridia,
+            1 => Guild::Greennill,
+            2 => Guild::Skyly,
+            3 => Guild::Bluefull,
+            4 => Guild::Purplenum,
+            5 => Guild::Pinkal,
+            6 => Guild::Redria,
+            7 => Guild::Oran,
+            8 => Guild::Yellowboze,
+            9 => Guild::Whitill,
+            _ => unreachable!(),
+        }
+    }
+
     /// Get the numeric ID for this guild (0-9)
     pub fn id(&self) -> u32 {
         match self {
@@ -129,34 +428,48 @@ impl Guild {
     }
 
     /// Get the most common drop for this guild
-    pub fn common_drop(&self) -> (&'static str, u32) {
+    ///
+    /// Yellowboze has no standout weapon, so it returns
+    /// [`WeaponDrop::AllEqual`].
+    pub fn common_drop(&self) -> WeaponDrop {
+        WeaponDrop::from_rates(self.common_weapon(), &self.drop_rates())
+    }
+
+    /// Get the rarest drop for this guild
+    ///
+    /// Yellowboze has no standout weapon, so it returns
+    /// [`WeaponDrop::AllEqual`].
+    pub fn rare_drop(&self) -> WeaponDrop {
+        WeaponDrop::from_rates(self.rare_weapon(), &self.drop_rates())
+    }
+
+    fn common_weapon(&self) -> Option<WeaponType> {
         match self {
-            Guild::Viridia => ("Partisans", 10),
-            Guild::Greennill => ("Rifles", 13),
-            Guild::Skyly => ("Swords", 13),
-            Guild::Bluefull => ("Partisans", 13),
-            Guild::Purplenum => ("Machineguns", 13),
-            Guild::Pinkal => ("Wands", 13),
-            Guild::Redria => ("Slicers", 10),
-            Guild::Oran => ("Daggers", 13),
-            Guild::Yellowboze => ("All Equal", 0),
-            Guild::Whitill => ("Machineguns", 10),
+            Guild::Viridia => Some(WeaponType::Partisans),
+            Guild::Greennill => Some(WeaponType::Rifles),
+            Guild::Skyly => Some(WeaponType::Swords),
+            Guild::Bluefull => Some(WeaponType::Partisans),
+            Guild::Purplenum => Some(WeaponType::Machineguns),
+            Guild::Pinkal => Some(WeaponType::Wands),
+            Guild::Redria => Some(WeaponType::Slicers),
+            Guild::Oran => Some(WeaponType::Daggers),
+            Guild::Yellowboze => None,
+            Guild::Whitill => Some(WeaponType::Machineguns),
         }
     }
 
-    /// Get the rarest drop for this guild
-    pub fn rare_drop(&self) -> (&'static str, u32) {
+    fn rare_weapon(&self) -> Option<WeaponType> {
         match self {
-            Guild::Viridia => ("Slicers", 1),
-            Guild::Greennill => ("Swords", 1),
-            Guild::Skyly => ("Machineguns", 1),
-            Guild::Bluefull => ("Wands", 1),
-            Guild::Purplenum => ("Daggers", 10),
-            Guild::Pinkal => ("Rifles", 1),
-            Guild::Redria => ("Daggers", 1),
-            Guild::Oran => ("Rods", 1),
-            Guild::Yellowboze => ("All Equal", 0),
-            Guild::Whitill => ("Shotguns", 1),
+            Guild::Viridia => Some(WeaponType::Slicers),
+            Guild::Greennill => Some(WeaponType::Swords),
+            Guild::Skyly => Some(WeaponType::Machineguns),
+            Guild::Bluefull => Some(WeaponType::Wands),
+            Guild::Purplenum => Some(WeaponType::Daggers),
+            Guild::Pinkal => Some(WeaponType::Rifles),
+            Guild::Redria => Some(WeaponType::Daggers),
+            Guild::Oran => Some(WeaponType::Rods),
+            Guild::Yellowboze => None,
+            Guild::Whitill => Some(WeaponType::Shotguns),
         }
     }
 
@@ -176,173 +489,76 @@ impl Guild {
         }
     }
 
-    /// Get the weapon drop rates for this guild
+    /// Get the weapon drop rates for this guild, in whole percentages
+    ///
+    /// The percentages are derived from [`Guild::drop_weights`] and always
+    /// add up to 100.
     pub fn drop_rates(&self) -> DropRates {
-        match self {
-            Guild::Viridia => DropRates {
-                sabers: 13,
-                swords: 6,
-                daggers: 7,
-                partisans: 10,
-                slicers: 1,
-                handguns: 13,
-                rifles: 6,
-                machineguns: 6,
-                shotguns: 11,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Greennill => DropRates {
-                sabers: 13,
-                swords: 1,
-                daggers: 10,
-                partisans: 6,
-                slicers: 6,
-                handguns: 13,
-                rifles: 13,
-                machineguns: 7,
-                shotguns: 4,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Skyly => DropRates {
-                sabers: 13,
-                swords: 13,
-                daggers: 7,
-                partisans: 6,
-                slicers: 6,
-                handguns: 13,
-                rifles: 10,
-                machineguns: 1,
-                shotguns: 4,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Bluefull => DropRates {
-                sabers: 13,
-                swords: 7,
-                daggers: 6,
-                partisans: 13,
-                slicers: 6,
-                handguns: 13,
-                rifles: 7,
-                machineguns: 7,
-                shotguns: 4,
-                canes: 13,
-                rods: 10,
-                wands: 1,
-            },
-            Guild::Purplenum => DropRates {
-                sabers: 13,
-                swords: 3,
-                daggers: 10,
-                partisans: 3,
-                slicers: 6,
-                handguns: 13,
-                rifles: 7,
-                machineguns: 13,
-                shotguns: 5,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Pinkal => DropRates {
-                sabers: 13,
-                swords: 6,
-                daggers: 7,
-                partisans: 10,
-                slicers: 6,
-                handguns: 13,
-                rifles: 1,
-                machineguns: 7,
-                shotguns: 4,
-                canes: 13,
-                rods: 7,
-                wands: 13,
-            },
-            Guild::Redria => DropRates {
-                sabers: 13,
-                swords: 7,
-                daggers: 1,
-                partisans: 7,
-                slicers: 10,
-                handguns: 13,
-                rifles: 7,
-                machineguns: 7,
-                shotguns: 8,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Oran => DropRates {
-                sabers: 13,
-                swords: 8,
-                daggers: 13,
-                partisans: 7,
-                slicers: 6,
-                handguns: 13,
-                rifles: 7,
-                machineguns: 7,
-                shotguns: 4,
-                canes: 13,
-                rods: 1,
-                wands: 8,
-            },
-            Guild::Yellowboze => DropRates {
-                sabers: 13,
-                swords: 7,
-                daggers: 7,
-                partisans: 7,
-                slicers: 7,
-                handguns: 13,
-                rifles: 7,
-                machineguns: 7,
-                shotguns: 5,
-                canes: 13,
-                rods: 7,
-                wands: 7,
-            },
-            Guild::Whitill => DropRates {
-                sabers: 13,
-                swords: 6,
-                daggers: 6,
-                partisans: 6,
-                slicers: 13,
-                handguns: 13,
-                rifles: 6,
-                machineguns: 10,
-                shotguns: 1,
-                canes: 13,
-                rods: 7,
-                wands: 6,
-            },
-        }
+        self.drop_weights().percentages()
+    }
+
+    /// Get the relative weight of each weapon type in this guild's drops
+    ///
+    /// The crate doesn't ship the game's item tables, so these weights are
+    /// the published percentages, which already total 100 for every guild.
+    /// The fractions are therefore no more exact than [`Guild::drop_rates`].
+    /// For the game's own weights, read `ItemPT.afs` or `ItemPT.gsl` with
+    /// [`ItemPatternSet`] and use [`ItemPattern::drop_weights`].
+    pub fn drop_weights(&self) -> DropWeights {
+        // Sabers, Swords, Daggers, Partisans, Slicers, Handguns,
+        // Rifles, Machineguns, Shotguns, Canes, Rods, Wands
+        let weights = match self {
+            Guild::Viridia => [13, 6, 7, 10, 1, 13, 6, 6, 11, 13, 7, 7],
+            Guild::Greennill => [13, 1, 10, 6, 6, 13, 13, 7, 4, 13, 7, 7],
+            Guild::Skyly => [13, 13, 7, 6, 6, 13, 10, 1, 4, 13, 7, 7],
+            Guild::Bluefull => [13, 7, 6, 13, 6, 13, 7, 7, 4, 13, 10, 1],
+            Guild::Purplenum => [13, 3, 10, 3, 6, 13, 7, 13, 5, 13, 7, 7],
+            Guild::Pinkal => [13, 6, 7, 10, 6, 13, 1, 7, 4, 13, 7, 13],
+            Guild::Redria => [13, 7, 1, 7, 10, 13, 7, 7, 8, 13, 7, 7],
+            Guild::Oran => [13, 8, 13, 7, 6, 13, 7, 7, 4, 13, 1, 8],
+            Guild::Yellowboze => [13, 7, 7, 7, 7, 13, 7, 7, 5, 13, 7, 7],
+            Guild::Whitill => [13, 6, 6, 6, 13, 13, 6, 10, 1, 13, 7, 6],
+        };
+        DropWeights::new(weights).expect("built-in drop tables are valid")
     }
 }
 
 impl fmt::Display for Guild {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (common_name, common_pct) = self.common_drop();
-        let (rare_name, rare_pct) = self.rare_drop();
-        write!(
-            f,
-            "Guild: {}, Class: {}, Common: {} ({}%), Rare: {} ({}%), MAG: {}",
-            self.name(),
-            self.best_class(),
-            common_name,
-            common_pct,
-            rare_name,
-            rare_pct,
-            self.mag_type()
-        )
+        self.report().fmt(f)
     }
 }
 
-/// Drop rates for weapons by guild
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl TryFrom<u32> for Guild {
+    type Error = PsoidError;
+
+    fn try_from(id: u32) -> Result<Self, Self::Error> {
+        if id > 9 {
+            return Err(PsoidError::InvalidGuildId(id));
+        }
+        Ok(Guild::from_id(id))
+    }
+}
+
+/// Parse a guild name ignoring case ("redria", "Redria") or its ID ("6")
+impl FromStr for Guild {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Ok(id) = value.parse::<u32>() {
+            return Guild::try_from(id);
+        }
+        Guild::ALL
+            .into_iter()
+            .find(|guild| guild.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| PsoidError::UnknownGuild(value.to_string()))
+    }
+}
+
+/// Drop rates for weapons by guild, in whole percentages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DropRates {
     pub sabers: u32,
     pub swords: u32,
@@ -359,7 +575,15 @@ pub struct DropRates {
 }
 
 /// Get character value for BlueBurst calculation
-fn get_blueburst_char_value(ch: char) -> Result<u32, String> {
+///
+/// `index` is the character's position in the name and is only used to
+/// report where an unsupported character was found.
+///
+/// Only ASCII is covered. Support for Japanese names (kana, half-width
+/// katakana and full-width characters) is deferred: it needs the client's
+/// value table and names checked in-game, and neither is available, so those
+/// names are rejected rather than guessed at.
+fn get_blueburst_char_value(index: usize, ch: char) -> Result<u32, PsoidError> {
     let value = match ch {
         'A' => 5,
         'B' => 6,
@@ -456,21 +680,44 @@ fn get_blueburst_char_value(ch: char) -> Result<u32, String> {
         '/' => 7,
         '?' => 3,
         ' ' => 2,
-        _ => return Err(format!("Unsupported character: {}", ch)),
+        _ => return Err(PsoidError::UnsupportedCharacter { index, ch }),
     };
     Ok(value)
 }
 
+/// Get every printable character the algorithm accepts in a name
+fn supported_chars(algorithm: &dyn SectionIdAlgorithm) -> Vec<char> {
+    (' '..='~')
+        .filter(|&ch| algorithm.char_value(0, ch).is_ok())
+        .collect()
+}
+
+/// Check a name isn't empty or longer than [`MAX_NAME_LEN`], measured with
+/// the algorithm's [`SectionIdAlgorithm::char_len`]
+fn check_name<A: SectionIdAlgorithm + ?Sized>(algorithm: &A, name: &str) -> Result<(), PsoidError> {
+    if name.is_empty() {
+        return Err(PsoidError::EmptyName);
+    }
+    let len = name.chars().map(|ch| algorithm.char_len(ch)).sum();
+    if len > MAX_NAME_LEN {
+        return Err(PsoidError::NameTooLong {
+            len,
+            max: MAX_NAME_LEN,
+        });
+    }
+    Ok(())
+}
+
 /// Calculate Section ID for a character name
 ///
 /// # Arguments
 /// * `name` - The character name (must be at most 12 characters)
-/// * `version` - The game version to use for calculation
+/// * `version` - The game version whose [`SectionIdAlgorithm`] is used
 /// * `class` - Character class (required for BlueBurst, ignored for V1/V2)
 ///
 /// # Returns
 /// * `Ok(Guild)` - The guild with all associated information
-/// * `Err(String)` - Error message if calculation fails
+/// * `Err(PsoidError)` - The reason the calculation failed
 ///
 /// # Examples
 ///
@@ -487,52 +734,134 @@ pub fn calculate(
     name: &str,
     version: GameVersion,
     class: Option<CharacterClass>,
-) -> Result<Guild, String> {
-    if name.is_empty() {
-        return Err("Name cannot be empty".to_string());
-    }
-    if name.len() > 12 {
-        return Err("Name must be at most 12 characters long".to_string());
-    }
+) -> Result<Guild, PsoidError> {
+    calculate_with(name, version.algorithm(), class)
+}
 
-    let id = match version {
-        GameVersion::V1 | GameVersion::V2 => {
-            if !name.is_ascii() {
-                return Err("Name must contain only ASCII characters".to_string());
-            }
+/// Calculate Section ID for a character name with the given algorithm
+///
+/// # Examples
+///
+/// ```
+/// use psoid::{calculate_with, GameVersion, Region};
+///
+/// let algorithm = Region::Japan.algorithm(GameVersion::V2);
+/// assert_eq!(calculate_with("リコ", algorithm, None).unwrap().name(), "Skyly");
+/// ```
+pub fn calculate_with(
+    name: &str,
+    algorithm: &dyn SectionIdAlgorithm,
+    class: Option<CharacterClass>,
+) -> Result<Guild, PsoidError> {
+    algorithm.section_id(name, class)
+}
 
-            let sum: u32 = name.bytes().map(|b| b as u32).sum();
+/// A single character's contribution to the Section ID sum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharStep {
+    /// Position of the character in the name
+    pub index: usize,
+    /// The character itself
+    pub ch: char,
+    /// The value added to the sum (ASCII byte for V1/V2, table value for Blue Burst)
+    pub value: u32,
+    /// The sum after adding this character
+    pub running_sum: u32,
+}
 
-            sum % 10
-        }
-        GameVersion::BlueBurst => {
-            let mut sum: u32 = 0;
+/// Step-by-step trace of how a name's Section ID was calculated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub version: GameVersion,
+    pub class: Option<CharacterClass>,
+    /// One entry per character in the name
+    pub steps: Vec<CharStep>,
+    /// Value added by the class (always 0 for V1/V2)
+    pub class_offset: u32,
+    /// Total of every character value plus the class offset
+    pub sum: u32,
+    /// The guild selected by `sum % 10`
+    pub guild: Guild,
+}
 
-            for ch in name.chars() {
-                sum += get_blueburst_char_value(ch)?;
-            }
+/// Explain how the Section ID for a character name is calculated
+///
+/// Takes the same arguments as [`calculate`] and fails in the same cases, but
+/// returns every character's contribution, the class offset and the final
+/// `sum % 10` instead of just the guild.
+///
+/// # Examples
+///
+/// ```
+/// use psoid::{explain, GameVersion};
+///
+/// let explanation = explain("Bob", GameVersion::V1, None).unwrap();
+/// assert_eq!(explanation.steps[0].value, 66);
+/// assert_eq!(explanation.sum, 275);
+/// assert_eq!(explanation.guild.name(), "Pinkal");
+///
+/// println!("{}", explanation);
+/// ```
+pub fn explain(
+    name: &str,
+    version: GameVersion,
+    class: Option<CharacterClass>,
+) -> Result<Explanation, PsoidError> {
+    explain_with(name, version, version.algorithm(), class)
+}
 
-            if let Some(class_obj) = class {
-                sum += class_obj.blueburst_offset();
-            }
+/// Explain a Section ID calculated with the given algorithm
+///
+/// `version` is only recorded in the [`Explanation`]; the values come from
+/// `algorithm`.
+pub fn explain_with(
+    name: &str,
+    version: GameVersion,
+    algorithm: &dyn SectionIdAlgorithm,
+    class: Option<CharacterClass>,
+) -> Result<Explanation, PsoidError> {
+    let (steps, class_offset, sum) = algorithm::trace(algorithm, name, class)?;
+    Ok(Explanation {
+        version,
+        class,
+        steps,
+        class_offset,
+        sum,
+        guild: Guild::from_id(sum % 10),
+    })
+}
 
-            sum % 10
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  #  Char  Value    Sum")?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "{:>3}  {:<4}  {:>5}  {:>5}",
+                step.index,
+                format!("'{}'", step.ch),
+                step.value,
+                step.running_sum
+            )?;
         }
-    };
-
-    Ok(match id {
-        0 => Guild::Viridia,
-        1 => Guild::Greennill,
-        2 => Guild::Skyly,
-        3 => Guild::Bluefull,
-        4 => Guild::Purplenum,
-        5 => Guild::Pinkal,
-        6 => Guild::Redria,
-        7 => Guild::Oran,
-        8 => Guild::Yellowboze,
-        9 => Guild::Whitill,
-        _ => unreachable!(),
-    })
+        let uses_class = self.version == GameVersion::BlueBurst || self.class_offset != 0;
+        if let Some(class) = self.class.filter(|_| uses_class) {
+            writeln!(
+                f,
+                "Class offset ({}): +{} = {}",
+                class.name(),
+                self.class_offset,
+                self.sum
+            )?;
+        }
+        write!(
+            f,
+            "Section ID: {} % 10 = {} ({})",
+            self.sum,
+            self.guild.id(),
+            self.guild.name()
+        )
+    }
 }
 
 #[cfg(test)]
@@ -635,7 +964,9 @@ mod tests {
         for version in [GameVersion::V1, GameVersion::V2] {
             let result = calculate("", version, None);
             assert!(result.is_err());
-            assert_eq!(result.unwrap_err(), "Name cannot be empty");
+            let err = result.unwrap_err();
+            assert_eq!(err, PsoidError::EmptyName);
+            assert_eq!(err.to_string(), "Name cannot be empty");
         }
     }
 
@@ -644,10 +975,9 @@ mod tests {
         for version in [GameVersion::V1, GameVersion::V2] {
             let result = calculate("thisnameistoolong", version, None);
             assert!(result.is_err());
-            assert_eq!(
-                result.unwrap_err(),
-                "Name must be at most 12 characters long"
-            );
+            let err = result.unwrap_err();
+            assert_eq!(err, PsoidError::NameTooLong { len: 17, max: 12 });
+            assert_eq!(err.to_string(), "Name must be at most 12 characters long");
         }
     }
 
@@ -656,21 +986,47 @@ mod tests {
         for version in [GameVersion::V1, GameVersion::V2] {
             let result = calculate("café", version, None);
             assert!(result.is_err());
+            let err = result.unwrap_err();
+            assert_eq!(err, PsoidError::NonAscii { index: 3, ch: 'é' });
+            assert_eq!(err.to_string(), "Name must contain only ASCII characters");
+        }
+    }
+
+    #[test]
+    fn test_blueburst_unsupported_character() {
+        let result = calculate("Bob\tX", GameVersion::BlueBurst, None);
+        assert_eq!(
+            result.unwrap_err(),
+            PsoidError::UnsupportedCharacter { index: 3, ch: '\t' }
+        );
+    }
+
+    #[test]
+    fn test_blueburst_japanese_unsupported() {
+        for (name, index, ch) in [("はなこ", 0, 'は'), ("Rico２", 4, '２'), ("ﾘｺ", 0, 'ﾘ')]
+        {
             assert_eq!(
-                result.unwrap_err(),
-                "Name must contain only ASCII characters"
+                calculate(name, GameVersion::BlueBurst, None).unwrap_err(),
+                PsoidError::UnsupportedCharacter { index, ch }
             );
         }
     }
 
     #[test]
     fn test_guild_info() {
-        for version in [GameVersion::V1, GameVersion::V2] {
+        for version in [GameVersion::V1, GameVersion::V2, GameVersion::GameCube] {
             let guild = calculate("Testing", version, None).unwrap();
             assert_eq!(guild.id(), 4);
             assert_eq!(guild.name(), "Purplenum");
-            assert_eq!(guild.common_drop(), ("Machineguns", 13));
-            assert_eq!(guild.rare_drop(), ("Daggers", 10));
+            assert_eq!(
+                guild.common_drop(),
+                WeaponDrop::Weapon {
+                    weapon: WeaponType::Machineguns,
+                    percent: 13
+                }
+            );
+            assert_eq!(guild.rare_drop().weapon(), Some(WeaponType::Daggers));
+            assert_eq!(guild.rare_drop().percent(), Some(10));
             assert_eq!(guild.mag_type(), "B");
         }
     }
@@ -697,18 +1053,7 @@ mod tests {
 
     #[test]
     fn test_all_guilds() {
-        let guilds = [
-            Guild::Viridia,
-            Guild::Greennill,
-            Guild::Skyly,
-            Guild::Bluefull,
-            Guild::Purplenum,
-            Guild::Pinkal,
-            Guild::Redria,
-            Guild::Oran,
-            Guild::Yellowboze,
-            Guild::Whitill,
-        ];
+        let guilds = Guild::ALL;
 
         for (index, guild) in guilds.iter().enumerate() {
             assert_eq!(guild.id(), index as u32);
@@ -824,6 +1169,115 @@ mod tests {
         assert_eq!(guild.name(), "Pinkal");
     }
 
+    #[test]
+    fn test_explain_v1() {
+        let explanation = explain("Bob", GameVersion::V1, Some(CharacterClass::HUmar)).unwrap();
+        let values: Vec<u32> = explanation.steps.iter().map(|s| s.value).collect();
+        let sums: Vec<u32> = explanation.steps.iter().map(|s| s.running_sum).collect();
+
+        assert_eq!(values, vec![66, 111, 98]);
+        assert_eq!(sums, vec![66, 177, 275]);
+        assert_eq!(explanation.class_offset, 0);
+        assert_eq!(explanation.sum, 275);
+        assert_eq!(explanation.guild, Guild::Pinkal);
+    }
+
+    #[test]
+    fn test_explain_blueburst() {
+        let explanation =
+            explain("Tank", GameVersion::BlueBurst, Some(CharacterClass::HUcast)).unwrap();
+        let values: Vec<u32> = explanation.steps.iter().map(|s| s.value).collect();
+
+        assert_eq!(values, vec![4, 7, 0, 7]);
+        assert_eq!(explanation.class_offset, 7);
+        assert_eq!(explanation.sum, 25);
+        assert_eq!(explanation.guild, Guild::Pinkal);
+
+        let table = explanation.to_string();
+        assert!(table.contains("Class offset (HUcast): +7 = 25"));
+        assert!(table.ends_with("Section ID: 25 % 10 = 5 (Pinkal)"));
+    }
+
+    #[test]
+    fn test_explain_matches_calculate() {
+        for version in GameVersion::ALL {
+            for name in ["Testing", "PSO Player", "Corey", "x"] {
+                let class = Some(CharacterClass::FOnewm);
+                assert_eq!(
+                    explain(name, version, class).unwrap().guild,
+                    calculate(name, version, class).unwrap()
+                );
+            }
+            assert_eq!(
+                explain("", version, None).unwrap_err(),
+                PsoidError::EmptyName
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        for text in ["v1", "V1", "1", "version 1"] {
+            assert_eq!(text.parse::<GameVersion>().unwrap(), GameVersion::V1);
+        }
+        for text in ["v2", "2", "version 2"] {
+            assert_eq!(text.parse::<GameVersion>().unwrap(), GameVersion::V2);
+        }
+        for text in ["ep1&2", "Ep 1&2", "gc", "GameCube"] {
+            assert_eq!(text.parse::<GameVersion>().unwrap(), GameVersion::GameCube);
+        }
+        for text in ["bb", "BB", "blue burst", "BlueBurst", "blue-burst"] {
+            assert_eq!(text.parse::<GameVersion>().unwrap(), GameVersion::BlueBurst);
+        }
+        assert_eq!(
+            "v3".parse::<GameVersion>().unwrap_err(),
+            PsoidError::UnknownVersion("v3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_class() {
+        for text in ["humar", "HUmar", "HUMAR", " HUmar "] {
+            assert_eq!(
+                text.parse::<CharacterClass>().unwrap(),
+                CharacterClass::HUmar
+            );
+        }
+        for class in CharacterClass::ALL {
+            assert_eq!(class.to_string().parse::<CharacterClass>().unwrap(), class);
+        }
+        assert_eq!(
+            "HUnewm".parse::<CharacterClass>().unwrap_err(),
+            PsoidError::UnknownClass("HUnewm".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_guild() {
+        for guild in Guild::ALL {
+            assert_eq!(guild.name().parse::<Guild>().unwrap(), guild);
+            assert_eq!(guild.name().to_uppercase().parse::<Guild>().unwrap(), guild);
+            assert_eq!(guild.id().to_string().parse::<Guild>().unwrap(), guild);
+            assert_eq!(Guild::try_from(guild.id()).unwrap(), guild);
+        }
+        assert_eq!(
+            Guild::try_from(10).unwrap_err(),
+            PsoidError::InvalidGuildId(10)
+        );
+        assert_eq!(
+            "Purple".parse::<Guild>().unwrap_err(),
+            PsoidError::UnknownGuild("Purple".to_string())
+        );
+    }
+
+    #[test]
+    fn test_version_and_class_display() {
+        assert_eq!(GameVersion::BlueBurst.to_string(), "BlueBurst");
+        assert_eq!(CharacterClass::FOnewearl.to_string(), "FOnewearl");
+        assert_eq!(GameVersion::ALL.len(), 4);
+        assert_eq!(CharacterClass::ALL.len(), 12);
+    }
+
     #[test]
     fn test_character_class_offsets() {
         assert_eq!(CharacterClass::HUmar.blueburst_offset(), 5);
@@ -0,0 +1,923 @@
+use psoid::{
+    explain_with, find_names_with, render_names, Algorithms, BoxRareTable, CharacterClass,
+    Difficulty, DropTableSet, Episode, GameVersion, Guild, ItemPatternSet, OutputFormat,
+    PsoidError, RareItemNames, RareItemSet, RareTable, Region, RosterFormat, SearchOptions,
+    SpawnTable, WeaponType,
+};
+use std::env;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+
+/// Exit code for a name the game rejects or a search that found nothing
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for bad command-line arguments
+const EXIT_USAGE: i32 = 2;
+
+/// Why the program is exiting early
+enum CliError {
+    /// The arguments were wrong; print the message and the command's usage
+    Usage(String),
+    /// The arguments were fine but the command failed
+    Failed(String),
+}
+
+/// An option that takes a value, e.g. `--game bb`
+struct Flag {
+    long: &'static str,
+    short: char,
+    value: &'static str,
+    help: &'static str,
+}
+
+const GAME_FLAG: Flag = Flag {
+    long: "game",
+    short: 'g',
+    value: "VERSION",
+    help: "Game version: v1, v2, gc (or ep1&2, gamecube) or bb (or blueburst) (default: v1)",
+};
+
+const REGION_FLAG: Flag = Flag {
+    long: "region",
+    short: 'R',
+    value: "REGION",
+    help: "Region: international or japan, which allows kana names before Blue Burst (default: international)",
+};
+
+const CLASS_FLAG: Flag = Flag {
+    long: "class",
+    short: 'c',
+    value: "CLASS",
+    help: "Character class, only used by Blue Burst (e.g. HUmar)",
+};
+
+const DIFFICULTY_FLAG: Flag = Flag {
+    long: "difficulty",
+    short: 'd',
+    value: "DIFFICULTY",
+    help: "Difficulty: normal, hard, very-hard or ultimate (default: normal)",
+};
+
+const TABLE_FLAG: Flag = Flag {
+    long: "table",
+    short: 't',
+    value: "FILE",
+    help: "Your rare drop table: a CSV (episode,difficulty,enemy,guild,item,rate) or an ItemRT.afs/.gsl; none is bundled",
+};
+
+const NAMES_FLAG: Flag = Flag {
+    long: "names",
+    short: 'N',
+    value: "FILE",
+    help: "Item and enemy names for an ItemRT --table, as a CSV (kind,id,name)",
+};
+
+const RATES_FLAG: Flag = Flag {
+    long: "rates",
+    short: 'r',
+    value: "FILE",
+    help: "Drop tables to use instead of the built-in rates: a set (.toml or .json) or an ItemPT.afs/.gsl (its Episode I tables)",
+};
+
+const FORMAT_FLAG: Flag = Flag {
+    long: "format",
+    short: 'f',
+    value: "FORMAT",
+    help: "Output format: text, json, csv or tsv (default: text)",
+};
+
+/// A subcommand of the `psoid` binary
+struct Command {
+    name: &'static str,
+    args: &'static str,
+    summary: &'static str,
+    flags: &'static [Flag],
+    run: fn(&Args) -> Result<(), CliError>,
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "calc",
+        args: "<name>",
+        summary: "Calculate the Section ID for a character name",
+        flags: &[
+            GAME_FLAG,
+            REGION_FLAG,
+            CLASS_FLAG,
+            DIFFICULTY_FLAG,
+            RATES_FLAG,
+            FORMAT_FLAG,
+        ],
+        run: run_calc,
+    },
+    Command {
+        name: "explain",
+        args: "<name>",
+        summary: "Show how each character adds up to the Section ID",
+        flags: &[
+            GAME_FLAG,
+            REGION_FLAG,
+            CLASS_FLAG,
+            DIFFICULTY_FLAG,
+            RATES_FLAG,
+        ],
+        run: run_explain,
+    },
+    Command {
+        name: "search",
+        args: "<guild>",
+        summary: "Find names that land on a Section ID",
+        flags: &[
+            GAME_FLAG,
+            REGION_FLAG,
+            CLASS_FLAG,
+            DIFFICULTY_FLAG,
+            RATES_FLAG,
+            FORMAT_FLAG,
+            Flag {
+                long: "prefix",
+                short: 'p',
+                value: "TEXT",
+                help: "Only find names starting with TEXT",
+            },
+            Flag {
+                long: "charset",
+                short: 's',
+                value: "CHARS",
+                help: "Characters to add after the prefix (default: A-Z, a-z, 0-9)",
+            },
+            Flag {
+                long: "min-len",
+                short: 'm',
+                value: "N",
+                help: "Shortest name to find (default: 1)",
+            },
+            Flag {
+                long: "max-len",
+                short: 'M',
+                value: "N",
+                help: "Longest name to find (default: 12)",
+            },
+            Flag {
+                long: "limit",
+                short: 'n',
+                value: "N",
+                help: "Stop after N names (default: 20)",
+            },
+        ],
+        run: run_search,
+    },
+    Command {
+        name: "guild",
+        args: "<guild>",
+        summary: "Show a Section ID's drops, MAG type and drop rates",
+        flags: &[GAME_FLAG, DIFFICULTY_FLAG, RATES_FLAG, FORMAT_FLAG],
+        run: run_guild,
+    },
+    Command {
+        name: "recommend",
+        args: "<weapon[=weight]>...",
+        summary: "Rank Section IDs by the weapons you want (e.g. partisans=3 slicers)",
+        flags: &[GAME_FLAG, DIFFICULTY_FLAG, RATES_FLAG],
+        run: run_recommend,
+    },
+    Command {
+        name: "rares",
+        args: "<guild>",
+        summary: "List a Section ID's rare drops from your rare drop table",
+        flags: &[TABLE_FLAG, NAMES_FLAG, DIFFICULTY_FLAG],
+        run: run_rares,
+    },
+    Command {
+        name: "who-drops",
+        args: "<item>",
+        summary: "Find which enemies and Section IDs drop an item in your rare drop table",
+        flags: &[TABLE_FLAG, NAMES_FLAG],
+        run: run_who_drops,
+    },
+    Command {
+        name: "hunt",
+        args: "<item>...",
+        summary: "Rank Section ID, difficulty and area by expected drops of the items",
+        flags: &[
+            TABLE_FLAG,
+            NAMES_FLAG,
+            GAME_FLAG,
+            RATES_FLAG,
+            Flag {
+                long: "spawns",
+                short: 's',
+                value: "FILE",
+                help: "Your enemy and box counts per area CSV (area,enemy,count); none is bundled",
+            },
+            Flag {
+                long: "boxes",
+                short: 'b',
+                value: "FILE",
+                help: "Your box rare table CSV (area,difficulty,guild,item,rate); none is bundled",
+            },
+            Flag {
+                long: "limit",
+                short: 'n',
+                value: "N",
+                help: "Show the best N plans (default: 10)",
+            },
+        ],
+        run: run_hunt,
+    },
+    Command {
+        name: "rare-diff",
+        args: "<old> <new>",
+        summary: "List the rares that differ between two ItemRT.afs or ItemRT.gsl files",
+        flags: &[],
+        run: run_rare_diff,
+    },
+    Command {
+        name: "compare",
+        args: "<name>",
+        summary: "Show a name's Section ID in every version and class, marking changes",
+        flags: &[REGION_FLAG, DIFFICULTY_FLAG, RATES_FLAG, FORMAT_FLAG],
+        run: run_compare,
+    },
+    Command {
+        name: "batch",
+        args: "<roster>",
+        summary: "Calculate every character in a CSV or JSON Lines roster",
+        flags: &[
+            Flag {
+                long: "game",
+                short: 'g',
+                value: "VERSION",
+                help: "Version for rows without one (default: v1)",
+            },
+            REGION_FLAG,
+            DIFFICULTY_FLAG,
+            RATES_FLAG,
+            Flag {
+                long: "input",
+                short: 'i',
+                value: "FORMAT",
+                help: "Roster format: csv or jsonl (default: from the file extension)",
+            },
+            FORMAT_FLAG,
+        ],
+        run: run_batch,
+    },
+    Command {
+        name: "classes",
+        args: "",
+        summary: "List the character classes and their Blue Burst offsets",
+        flags: &[],
+        run: run_classes,
+    },
+];
+
+/// Arguments given to a subcommand, after flags have been separated out
+struct Args {
+    positional: Vec<String>,
+    values: Vec<(&'static str, String)>,
+}
+
+impl Args {
+    /// Split `args` into positional arguments and the command's flags
+    fn parse(command: &Command, args: &[String]) -> Result<Args, CliError> {
+        let mut positional = Vec::new();
+        let mut values = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                positional.extend(iter.cloned());
+                break;
+            }
+
+            let (key, inline) = if let Some(long) = arg.strip_prefix("--") {
+                match long.split_once('=') {
+                    Some((key, value)) => (key, Some(value.to_string())),
+                    None => (long, None),
+                }
+            } else if let Some(short) = arg.strip_prefix('-').filter(|short| !short.is_empty()) {
+                // Anything else starting with '-' is an option too, so a
+                // mistyped one isn't taken for a name; names go after `--`
+                (short, None)
+            } else {
+                positional.push(arg.clone());
+                continue;
+            };
+
+            let flag = command
+                .flags
+                .iter()
+                .find(|flag| flag.long == key || key.chars().eq([flag.short]))
+                .ok_or_else(|| {
+                    CliError::Usage(format!(
+                        "Unknown option: {} (put arguments starting with '-' after `--`)",
+                        arg
+                    ))
+                })?;
+
+            let value = match inline {
+                Some(value) => value,
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| CliError::Usage(format!("Missing value for --{}", flag.long)))?,
+            };
+
+            values.push((flag.long, value));
+        }
+
+        Ok(Args { positional, values })
+    }
+
+    /// Get the value of a flag, if it was given (the last one wins)
+    fn value(&self, long: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(key, _)| *key == long)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the only positional argument, named `what` in error messages
+    fn single(&self, what: &str) -> Result<&str, CliError> {
+        match self.positional.as_slice() {
+            [value] => Ok(value),
+            [] => Err(CliError::Usage(format!("Missing {}", what))),
+            _ => Err(CliError::Usage(format!(
+                "Expected a single {}, got {} arguments (quote names with spaces)",
+                what,
+                self.positional.len()
+            ))),
+        }
+    }
+
+    fn game(&self) -> Result<GameVersion, CliError> {
+        self.value("game").map_or(Ok(GameVersion::V1), parse)
+    }
+
+    /// The algorithms of the `--region` release of every version
+    fn algorithms(&self) -> Result<Algorithms<'static>, CliError> {
+        let region = self
+            .value("region")
+            .map_or(Ok(Region::International), parse)?;
+        Ok(region.algorithms())
+    }
+
+    fn class(&self) -> Result<Option<CharacterClass>, CliError> {
+        self.value("class").map(parse).transpose()
+    }
+
+    fn difficulty(&self) -> Result<Difficulty, CliError> {
+        self.value("difficulty")
+            .map_or(Ok(Difficulty::Normal), parse)
+    }
+
+    /// Load `--table`, reading ItemRT archives by their extension and CSV
+    /// otherwise
+    fn rare_table(&self) -> Result<RareTable, CliError> {
+        let Some(path) = self.value("table") else {
+            return Err(CliError::Usage("Missing --table".to_string()));
+        };
+        let extension = path.to_lowercase();
+        if extension.ends_with(".afs") || extension.ends_with(".gsl") {
+            let names = self
+                .table_file("names", RareItemNames::from_csv)?
+                .unwrap_or_default();
+            return Ok(RareTable::from_item_rt(&read_rare_items(path)?, &names));
+        }
+        self.table_file("table", RareTable::from_csv)
+            .map(|table| table.unwrap_or_default())
+    }
+
+    /// Load the CSV file named by a flag, if it was given
+    fn table_file<T>(
+        &self,
+        long: &str,
+        load: fn(&str) -> Result<T, PsoidError>,
+    ) -> Result<Option<T>, CliError> {
+        let Some(path) = self.value(long) else {
+            return Ok(None);
+        };
+        let input = fs::read_to_string(path)
+            .map_err(|e| CliError::Failed(format!("Could not read {}: {}", path, e)))?;
+        load(&input)
+            .map(Some)
+            .map_err(|e| CliError::Failed(format!("{}: {}", path, e)))
+    }
+
+    /// Load the drop table set named by `--rates`, or the built-in tables
+    ///
+    /// An ItemPT archive's Episode I tables apply to the version it's from.
+    fn drop_tables(&self) -> Result<DropTableSet, CliError> {
+        let Some(path) = self.value("rates") else {
+            return Ok(DropTableSet::default());
+        };
+        let extension = path.to_lowercase();
+        if extension.ends_with(".afs") || extension.ends_with(".gsl") {
+            let bytes = fs::read(path)
+                .map_err(|e| CliError::Failed(format!("Could not read {}: {}", path, e)))?;
+            return if extension.ends_with(".afs") {
+                ItemPatternSet::from_afs(&bytes)
+            } else {
+                ItemPatternSet::from_gsl(&bytes)
+            }
+            .and_then(|set| DropTableSet::from_item_pt(&set, set.version(), Episode::I))
+            .map_err(|e| CliError::Failed(format!("{}: {}", path, e)));
+        }
+        let input = fs::read_to_string(path)
+            .map_err(|e| CliError::Failed(format!("Could not read {}: {}", path, e)))?;
+        load_drop_tables(path, &input).map_err(|e| CliError::Failed(format!("{}: {}", path, e)))
+    }
+
+    fn format(&self) -> Result<OutputFormat, CliError> {
+        self.value("format")
+            .map_or(Ok(OutputFormat::Text), parse_format)
+    }
+
+    fn number(&self, long: &str, default: usize) -> Result<usize, CliError> {
+        match self.value(long) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| CliError::Usage(format!("--{} must be a number", long))),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Read a drop table set, picking the format from the file extension
+#[cfg_attr(not(any(feature = "toml", feature = "json")), allow(unused_variables))]
+fn load_drop_tables(path: &str, input: &str) -> Result<DropTableSet, PsoidError> {
+    let path = path.to_lowercase();
+    #[cfg(feature = "toml")]
+    if path.ends_with(".toml") {
+        return DropTableSet::from_toml(input);
+    }
+    #[cfg(feature = "json")]
+    if path.ends_with(".json") {
+        return DropTableSet::from_json(input);
+    }
+    Err(PsoidError::InvalidDropTable(format!(
+        "can't read {} (TOML needs the toml feature and JSON the json feature)",
+        path
+    )))
+}
+
+/// Parse a version, class or guild argument, reporting bad values as usage errors
+fn parse<T: FromStr<Err = PsoidError>>(value: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|e: PsoidError| CliError::Usage(e.to_string()))
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, CliError> {
+    match value.to_lowercase().as_str() {
+        "text" | "txt" => Ok(OutputFormat::Text),
+        "json" | "jsonl" => Ok(OutputFormat::Json),
+        "csv" => Ok(OutputFormat::Csv),
+        "tsv" => Ok(OutputFormat::Tsv),
+        _ => Err(CliError::Usage(format!("Unknown format: {}", value))),
+    }
+}
+
+fn run_calc(args: &Args) -> Result<(), CliError> {
+    let name = args.single("character name")?;
+    let version = args.game()?;
+    let class = args.class()?;
+    let format = args.format()?;
+    let algorithm = args.algorithms()?.get(version);
+    let report = args
+        .drop_tables()?
+        .name_report_with(name, version, algorithm, args.difficulty()?, class)
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+
+    if format != OutputFormat::Text {
+        println!("{}", report.render(format));
+        return Ok(());
+    }
+
+    println!("Character Name: {}", name);
+    println!("Game Version  : {}", version.name());
+    if version == GameVersion::BlueBurst {
+        println!(
+            "Class         : {}",
+            class.map_or("N/A", |class| class.name())
+        );
+    }
+    println!();
+    println!("{}", report.guild);
+
+    Ok(())
+}
+
+fn run_explain(args: &Args) -> Result<(), CliError> {
+    let name = args.single("character name")?;
+    let (version, class) = (args.game()?, args.class()?);
+    let algorithm = args.algorithms()?.get(version);
+    let explanation = explain_with(name, version, algorithm, class)
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+    let report = args
+        .drop_tables()?
+        .report(explanation.guild, version, args.difficulty()?)
+        .map_err(|e| CliError::Usage(e.to_string()))?;
+
+    println!("{}", explanation);
+    println!();
+    println!("{}", report);
+
+    Ok(())
+}
+
+fn run_search(args: &Args) -> Result<(), CliError> {
+    let guild = parse::<Guild>(args.single("guild")?)?;
+    let defaults = SearchOptions::default();
+    let options = SearchOptions {
+        prefix: args.value("prefix").unwrap_or_default().to_string(),
+        charset: args
+            .value("charset")
+            .map_or(defaults.charset, |chars| chars.chars().collect()),
+        min_len: args.number("min-len", defaults.min_len)?,
+        max_len: args.number("max-len", defaults.max_len)?,
+    };
+    let limit = args.number("limit", 20)?;
+    let version = args.game()?;
+    let class = args.class()?;
+    let difficulty = args.difficulty()?;
+    let format = args.format()?;
+    let tables = args.drop_tables()?;
+    let algorithm = args.algorithms()?.get(version);
+    // Catch a difficulty the version doesn't have even when no report is shown
+    tables
+        .info(guild, version, difficulty)
+        .map_err(|e| CliError::Usage(e.to_string()))?;
+
+    let names: Vec<String> = find_names_with(guild, algorithm, class, &options)
+        .map_err(|e| CliError::Failed(e.to_string()))?
+        .take(limit)
+        .collect();
+
+    if format == OutputFormat::Text {
+        for name in &names {
+            println!("{}", name);
+        }
+    } else if !names.is_empty() {
+        let reports = names
+            .iter()
+            .map(|name| tables.name_report_with(name, version, algorithm, difficulty, class))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CliError::Failed(e.to_string()))?;
+        println!("{}", render_names(format, &reports));
+    }
+
+    if names.is_empty() {
+        return Err(CliError::Failed(format!(
+            "No names found for {}",
+            guild.name()
+        )));
+    }
+
+    Ok(())
+}
+
+fn run_guild(args: &Args) -> Result<(), CliError> {
+    let guild = parse::<Guild>(args.single("guild")?)?;
+    let format = args.format()?;
+    let info = args
+        .drop_tables()?
+        .info(guild, args.game()?, args.difficulty()?)
+        .map_err(|e| CliError::Usage(e.to_string()))?;
+    let report = info.report();
+
+    if format != OutputFormat::Text {
+        println!("{}", report.render(format));
+        return Ok(());
+    }
+
+    let rates = report.drop_rates;
+
+    println!("{}", report);
+    println!();
+    println!(
+        "Detailed Drop Rates ({}, {}):",
+        info.version, info.difficulty
+    );
+    for (weapon, rate) in rates.iter() {
+        println!("  {:<11} : {}%", weapon.name(), rate);
+    }
+
+    Ok(())
+}
+
+fn run_recommend(args: &Args) -> Result<(), CliError> {
+    if args.positional.is_empty() {
+        return Err(CliError::Usage("Missing weapon type".to_string()));
+    }
+
+    let mut wishlist = Vec::new();
+    for arg in &args.positional {
+        let (weapon, weight) = match arg.split_once('=') {
+            Some((weapon, weight)) => {
+                let weight = weight.trim().parse().map_err(|_| {
+                    CliError::Usage(format!("Weight for {} must be a number", weapon))
+                })?;
+                (weapon, weight)
+            }
+            None => (arg.as_str(), 1),
+        };
+        wishlist.push((parse::<WeaponType>(weapon)?, weight));
+    }
+
+    let ranking = args
+        .drop_tables()?
+        .rank_guilds(&wishlist, args.game()?, args.difficulty()?)
+        .map_err(|e| CliError::Usage(e.to_string()))?;
+    for (rank, entry) in ranking.iter().enumerate() {
+        println!("{:>2}. {}", rank + 1, entry);
+    }
+
+    Ok(())
+}
+
+fn run_rares(args: &Args) -> Result<(), CliError> {
+    let guild = parse::<Guild>(args.single("guild")?)?;
+    let difficulty = args.difficulty()?;
+    let table = args.rare_table()?;
+
+    let rares = table.rares_for(guild, difficulty);
+    if rares.is_empty() {
+        return Err(CliError::Failed(format!(
+            "No rare drops for {} on {}",
+            guild.name(),
+            difficulty
+        )));
+    }
+    for drop in rares {
+        println!("{}", drop);
+    }
+
+    Ok(())
+}
+
+fn run_who_drops(args: &Args) -> Result<(), CliError> {
+    let item = args.single("item name")?;
+    let table = args.rare_table()?;
+
+    let sources = table.who_drops(item);
+    if sources.is_empty() {
+        return Err(CliError::Failed(format!("Nothing drops {}", item)));
+    }
+    for drop in sources {
+        println!("{}", drop);
+    }
+
+    Ok(())
+}
+
+fn run_hunt(args: &Args) -> Result<(), CliError> {
+    if args.positional.is_empty() {
+        return Err(CliError::Usage("Missing item name".to_string()));
+    }
+    let wishlist: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+    let enemies = args.rare_table()?;
+    let spawns = args
+        .table_file("spawns", SpawnTable::from_csv)?
+        .ok_or_else(|| CliError::Usage("Missing --spawns".to_string()))?;
+    let boxes = args
+        .table_file("boxes", BoxRareTable::from_csv)?
+        .unwrap_or_default();
+    let limit = args.number("limit", 10)?;
+
+    let version = args.game()?;
+    let plans = args
+        .drop_tables()?
+        .plan_hunt(&wishlist, &enemies, &boxes, &spawns, version);
+    if plans.is_empty() {
+        return Err(CliError::Failed(format!(
+            "Nowhere in the spawn table drops {} in {}",
+            wishlist.join(", "),
+            version.name()
+        )));
+    }
+    for (rank, plan) in plans.iter().take(limit).enumerate() {
+        println!("{:>2}. {}", rank + 1, plan);
+    }
+
+    Ok(())
+}
+
+fn run_rare_diff(args: &Args) -> Result<(), CliError> {
+    let [old, new] = args.positional.as_slice() else {
+        return Err(CliError::Usage(
+            "Expected the old and new rare table files".to_string(),
+        ));
+    };
+    let changes = read_rare_items(old)?.diff(&read_rare_items(new)?);
+
+    if changes.is_empty() {
+        println!("No differences");
+    }
+    for change in changes {
+        println!("{}", change);
+    }
+
+    Ok(())
+}
+
+/// Read an ItemRT archive, as AFS if the file name says so and GSL otherwise
+fn read_rare_items(path: &str) -> Result<RareItemSet, CliError> {
+    let bytes =
+        fs::read(path).map_err(|e| CliError::Failed(format!("Could not read {}: {}", path, e)))?;
+    let set = if path.to_lowercase().ends_with(".afs") {
+        RareItemSet::from_afs(&bytes)
+    } else {
+        RareItemSet::from_gsl(&bytes)
+    };
+    set.map_err(|e| CliError::Failed(format!("{}: {}", path, e)))
+}
+
+fn run_compare(args: &Args) -> Result<(), CliError> {
+    let name = args.single("character name")?;
+    let format = args.format()?;
+    let comparison = args
+        .drop_tables()?
+        .compare_with(name, args.difficulty()?, &args.algorithms()?)
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+    println!("{}", comparison.render(format));
+
+    Ok(())
+}
+
+fn run_batch(args: &Args) -> Result<(), CliError> {
+    let path = args.single("roster file")?;
+    let input_format = match args.value("input") {
+        Some(value) => parse_roster_format(value)?,
+        None if path.ends_with(".jsonl") || path.ends_with(".json") => RosterFormat::JsonLines,
+        None => RosterFormat::Csv,
+    };
+    let format = args.format()?;
+
+    let input = fs::read_to_string(path)
+        .map_err(|e| CliError::Failed(format!("Could not read {}: {}", path, e)))?;
+    let roster = args
+        .drop_tables()?
+        .process_roster_with(
+            &input,
+            input_format,
+            args.game()?,
+            args.difficulty()?,
+            &args.algorithms()?,
+        )
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+
+    if !roster.reports.is_empty() {
+        println!("{}", render_names(format, &roster.reports));
+    }
+
+    if roster.errors.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!();
+    eprintln!(
+        "Skipped {} of {} rows:",
+        roster.errors.len(),
+        roster.errors.len() + roster.reports.len()
+    );
+    for error in &roster.errors {
+        match &error.name {
+            Some(name) => eprintln!("  line {} ({}): {}", error.line, name, error.error),
+            None => eprintln!("  line {}: {}", error.line, error.error),
+        }
+    }
+
+    Err(CliError::Failed(
+        "Some rows could not be processed".to_string(),
+    ))
+}
+
+fn parse_roster_format(value: &str) -> Result<RosterFormat, CliError> {
+    match value.to_lowercase().as_str() {
+        "csv" => Ok(RosterFormat::Csv),
+        "jsonl" | "json" | "ndjson" => Ok(RosterFormat::JsonLines),
+        _ => Err(CliError::Usage(format!("Unknown roster format: {}", value))),
+    }
+}
+
+fn run_classes(args: &Args) -> Result<(), CliError> {
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage("classes takes no arguments".to_string()));
+    }
+
+    for class in CharacterClass::ALL {
+        println!("{:<10} +{}", class.name(), class.blueburst_offset());
+    }
+
+    Ok(())
+}
+
+fn print_help() {
+    println!("Calculate Phantasy Star Online Section IDs");
+    println!();
+    println!("Usage: psoid <command> [options]");
+    println!();
+    println!("Commands:");
+    for command in COMMANDS {
+        println!("  {:<10} {}", command.name, command.summary);
+    }
+    println!();
+    println!("Run `psoid <command> --help` for a command's options.");
+    println!();
+    println!(
+        "Exit codes: 0 on success, {} if the command failed, {} for bad arguments.",
+        EXIT_FAILURE, EXIT_USAGE
+    );
+}
+
+fn print_command_help(command: &Command) {
+    println!("{}", command.summary);
+    println!();
+    println!(
+        "Usage: psoid {} {} {}",
+        command.name,
+        command.args,
+        if command.flags.is_empty() {
+            ""
+        } else {
+            "[options]"
+        }
+    );
+
+    if !command.flags.is_empty() {
+        println!();
+        println!("Options:");
+        for flag in command.flags {
+            let names = format!("-{}, --{} <{}>", flag.short, flag.long, flag.value);
+            println!("  {:<28} {}", names, flag.help);
+        }
+    }
+
+    if !command.args.is_empty() {
+        println!();
+        println!(
+            "Put `--` before arguments that start with '-': psoid {} -- {}",
+            command.name, command.args
+        );
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let Some(first) = args.first() else {
+        print_help();
+        process::exit(EXIT_USAGE);
+    };
+
+    match first.as_str() {
+        "-h" | "--help" | "help" => {
+            match args
+                .get(1)
+                .and_then(|name| COMMANDS.iter().find(|c| c.name == name))
+            {
+                Some(command) => print_command_help(command),
+                None => print_help(),
+            }
+            return;
+        }
+        "-V" | "--version" => {
+            println!("psoid {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        _ => {}
+    }
+
+    let Some(command) = COMMANDS.iter().find(|c| c.name == first) else {
+        eprintln!("Error: Unknown command: {}", first);
+        eprintln!("Run `psoid --help` for a list of commands.");
+        process::exit(EXIT_USAGE);
+    };
+
+    let rest = &args[1..];
+    let options = rest.split(|arg| arg == "--").next().unwrap_or_default();
+    if options.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print_command_help(command);
+        return;
+    }
+
+    let result = Args::parse(command, rest).and_then(|args| (command.run)(&args));
+
+    match result {
+        Ok(()) => {}
+        Err(CliError::Usage(message)) => {
+            eprintln!("Error: {}", message);
+            eprintln!();
+            eprintln!("Usage: psoid {} {}", command.name, command.args);
+            eprintln!("Run `psoid {} --help` for more information.", command.name);
+            process::exit(EXIT_USAGE);
+        }
+        Err(CliError::Failed(message)) => {
+            eprintln!("Error: {}", message);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
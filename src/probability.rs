@@ -0,0 +1,349 @@
+//! Exact drop probabilities from weapon weights
+//!
+//! A drop table is a weight per weapon type; each weapon's probability is its
+//! weight divided by the table's total. Percentages are derived from these
+//! fractions and rounded so that they always add up to exactly 100.
+//!
+//! The built-in guild weights are the published percentages, so for them the
+//! fractions add nothing; exact fractions come from weights read out of the
+//! game's item pattern tables with [`ItemPattern::drop_weights`](crate::ItemPattern::drop_weights).
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{DropWeights, Guild, WeaponType};
+//!
+//! let weights = Guild::Viridia.drop_weights();
+//! let partisans = weights.probability(WeaponType::Partisans);
+//! assert_eq!((partisans.numerator(), partisans.denominator()), (1, 10));
+//!
+//! let custom = DropWeights::new([1; 12]).unwrap();
+//! assert_eq!(custom.probability(WeaponType::Rods).to_string(), "1/12");
+//! assert_eq!(custom.percentages().iter().map(|(_, rate)| rate).sum::<u32>(), 100);
+//! ```
+
+use crate::{DropRates, PsoidError, WeaponType};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// An exact probability, stored as a fraction in lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Probability {
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Probability {
+    /// Create the probability `numerator / denominator`
+    ///
+    /// Returns `None` if the denominator is zero or the fraction is above one.
+    pub fn new(numerator: u32, denominator: u32) -> Option<Probability> {
+        Probability::from_u64(u64::from(numerator), u64::from(denominator))
+    }
+
+    /// Like [`Probability::new`], but also `None` if the fraction in lowest
+    /// terms doesn't fit in `u32`s
+    pub(crate) fn from_u64(numerator: u64, denominator: u64) -> Option<Probability> {
+        if denominator == 0 || numerator > denominator {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator);
+        Some(Probability {
+            numerator: u32::try_from(numerator / divisor).ok()?,
+            denominator: u32::try_from(denominator / divisor).ok()?,
+        })
+    }
+
+    pub fn numerator(&self) -> u32 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u32 {
+        self.denominator
+    }
+
+    /// Get the probability as a number between 0 and 1
+    pub fn as_f64(&self) -> f64 {
+        f64::from(self.numerator) / f64::from(self.denominator)
+    }
+
+    /// Get the probability as an unrounded percentage
+    pub fn percent(&self) -> f64 {
+        self.as_f64() * 100.0
+    }
+}
+
+impl fmt::Display for Probability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl Ord for Probability {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let left = u64::from(self.numerator) * u64::from(other.denominator);
+        let right = u64::from(other.numerator) * u64::from(self.denominator);
+        left.cmp(&right)
+    }
+}
+
+impl PartialOrd for Probability {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parse a fraction ("1/2048") or a percentage ("0.05%")
+impl FromStr for Probability {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let text = value.trim();
+        let invalid = || PsoidError::InvalidRate(value.to_string());
+
+        let probability = if let Some((numerator, denominator)) = text.split_once('/') {
+            let numerator = numerator.trim().parse().map_err(|_| invalid())?;
+            let denominator = denominator.trim().parse().map_err(|_| invalid())?;
+            Probability::from_u64(numerator, denominator)
+        } else if let Some(percent) = text.strip_suffix('%') {
+            let percent = percent.trim();
+            let (whole, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+            if whole.is_empty() && fraction.is_empty()
+                || !whole
+                    .chars()
+                    .chain(fraction.chars())
+                    .all(|ch| ch.is_ascii_digit())
+            {
+                return Err(invalid());
+            }
+            let numerator = format!("{}{}", whole, fraction)
+                .parse::<u64>()
+                .map_err(|_| invalid())?;
+            let denominator = u32::try_from(fraction.len())
+                .ok()
+                .and_then(|places| 10u64.checked_pow(places))
+                .and_then(|scale| scale.checked_mul(100))
+                .ok_or_else(invalid)?;
+            Probability::from_u64(numerator, denominator)
+        } else {
+            None
+        };
+
+        probability.ok_or_else(invalid)
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Relative weights for the twelve weapon types in a drop table
+///
+/// The weights built into the crate are the published percentage table, so
+/// each guild's total is 100. Tables from other sources can use any total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropWeights {
+    weights: [u32; 12],
+    total: u32,
+}
+
+impl DropWeights {
+    /// Create a table from weights in [`WeaponType::ALL`] order
+    ///
+    /// Fails if every weight is zero or the total doesn't fit in a `u32`.
+    pub fn new(weights: [u32; 12]) -> Result<DropWeights, PsoidError> {
+        let total = weights
+            .iter()
+            .try_fold(0u32, |total, &weight| total.checked_add(weight))
+            .ok_or_else(|| PsoidError::InvalidDropTable("total weight is too large".to_string()))?;
+        if total == 0 {
+            return Err(PsoidError::InvalidDropTable(
+                "every weight is zero".to_string(),
+            ));
+        }
+        Ok(DropWeights { weights, total })
+    }
+
+    /// Get the weight for a weapon type
+    pub fn weight(&self, weapon: WeaponType) -> u32 {
+        self.weights[weapon as usize]
+    }
+
+    /// Get the sum of every weight
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Get the exact probability that a weapon drop is of this type
+    pub fn probability(&self, weapon: WeaponType) -> Probability {
+        Probability::new(self.weight(weapon), self.total)
+            .expect("a weight never exceeds the non-zero total")
+    }
+
+    /// Iterate over every weapon type and its probability, in drop table order
+    pub fn iter(&self) -> impl Iterator<Item = (WeaponType, Probability)> + '_ {
+        WeaponType::ALL
+            .into_iter()
+            .map(move |weapon| (weapon, self.probability(weapon)))
+    }
+
+    /// Round the probabilities to whole percentages that add up to 100
+    ///
+    /// Each weapon gets its percentage rounded down, then the points left
+    /// over go to the weapons with the largest remainders (ties go to the
+    /// earlier weapon in the table).
+    pub fn percentages(&self) -> DropRates {
+        let total = u64::from(self.total);
+        let scaled: Vec<u64> = self
+            .weights
+            .iter()
+            .map(|&weight| u64::from(weight) * 100)
+            .collect();
+
+        let mut rates = DropRates::default();
+        for (weapon, value) in WeaponType::ALL.into_iter().zip(&scaled) {
+            rates[weapon] = (value / total) as u32;
+        }
+
+        let assigned: u32 = rates.iter().map(|(_, rate)| rate).sum();
+        let mut by_remainder: Vec<(WeaponType, u64)> = WeaponType::ALL
+            .into_iter()
+            .zip(&scaled)
+            .map(|(weapon, value)| (weapon, value % total))
+            .collect();
+        by_remainder.sort_by_key(|&(_, remainder)| std::cmp::Reverse(remainder));
+        for (weapon, _) in by_remainder.into_iter().take((100 - assigned) as usize) {
+            rates[weapon] += 1;
+        }
+
+        rates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guild;
+
+    #[test]
+    fn test_guild_tables_are_normalized() {
+        for guild in Guild::ALL {
+            let weights = guild.drop_weights();
+            assert_eq!(weights.total(), 100, "{}", guild.name());
+
+            // The fractions must add up to exactly one
+            let sum: u64 = weights
+                .iter()
+                .map(|(_, p)| {
+                    u64::from(p.numerator()) * u64::from(weights.total() / p.denominator())
+                })
+                .sum();
+            assert_eq!(sum, u64::from(weights.total()), "{}", guild.name());
+
+            let rates = guild.drop_rates();
+            assert_eq!(rates.iter().map(|(_, rate)| rate).sum::<u32>(), 100);
+            for (weapon, probability) in weights.iter() {
+                assert_eq!(
+                    rates[weapon] * probability.denominator(),
+                    probability.numerator() * 100
+                );
+            }
+        }
+    }
+
+    /// Build rates from literals in [`WeaponType::ALL`] order
+    fn rates(values: [u32; 12]) -> DropRates {
+        let mut rates = DropRates::default();
+        for (weapon, value) in WeaponType::ALL.into_iter().zip(values) {
+            rates[weapon] = value;
+        }
+        rates
+    }
+
+    #[test]
+    fn test_published_percentages() {
+        assert_eq!(
+            Guild::Viridia.drop_rates(),
+            rates([13, 6, 7, 10, 1, 13, 6, 6, 11, 13, 7, 7])
+        );
+        assert_eq!(
+            Guild::Purplenum.drop_rates(),
+            rates([13, 3, 10, 3, 6, 13, 7, 13, 5, 13, 7, 7])
+        );
+    }
+
+    #[test]
+    fn test_item_pt_percentages_need_rounding() {
+        // Viridia's hand-written Normal table in the ItemPT.afs fixture
+        // totals 160, so every weight is worth 0.625%. Flooring gives 94%;
+        // the six largest remainders (Slicers and Rifles .875, Daggers,
+        // Handguns and Wands .75, then Swords .625 ahead of Machineguns) get
+        // the rest. Rounding each weight on its own would give 102%.
+        let set = crate::ItemPatternSet::from_afs(include_bytes!("../tests/fixtures/ItemPT.afs"))
+            .unwrap();
+        let pattern = set
+            .get(crate::Episode::I, crate::Difficulty::Normal, Guild::Viridia)
+            .unwrap();
+        assert_eq!(
+            pattern.drop_rates().unwrap(),
+            rates([15, 6, 9, 11, 2, 14, 7, 5, 10, 12, 5, 4])
+        );
+    }
+
+    #[test]
+    fn test_percentages_round_to_100() {
+        let weights = DropWeights::new([1; 12]).unwrap();
+        let rates = weights.percentages();
+        assert_eq!(rates.iter().map(|(_, rate)| rate).sum::<u32>(), 100);
+        // 100 / 12 is 8 remainder 4, so the first four weapons get 9%
+        assert_eq!(rates.sabers, 9);
+        assert_eq!(rates.partisans, 9);
+        assert_eq!(rates.slicers, 8);
+
+        let weights = DropWeights::new([3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]).unwrap();
+        let rates = weights.percentages();
+        assert_eq!((rates.sabers, rates.wands, rates.rods), (75, 25, 0));
+    }
+
+    #[test]
+    fn test_probability() {
+        let probability = Probability::new(10, 100).unwrap();
+        assert_eq!(probability, Probability::new(1, 10).unwrap());
+        assert_eq!(probability.to_string(), "1/10");
+        assert_eq!(probability.as_f64(), 0.1);
+        assert_eq!(Probability::new(0, 7).unwrap().to_string(), "0/1");
+        assert_eq!(Probability::new(1, 0), None);
+        assert_eq!(Probability::new(2, 1), None);
+    }
+
+    #[test]
+    fn test_parse_and_order_probability() {
+        let fraction: Probability = "1/2048".parse().unwrap();
+        assert_eq!((fraction.numerator(), fraction.denominator()), (1, 2048));
+        let percent: Probability = "0.05%".parse().unwrap();
+        assert_eq!(percent, Probability::new(1, 2000).unwrap());
+        assert_eq!("25%".parse::<Probability>().unwrap().to_string(), "1/4");
+        assert!(percent > fraction);
+        assert!(Probability::new(1, 3).unwrap() < Probability::new(2, 5).unwrap());
+
+        for text in ["", "%", "1/0", "3/2", "abc", "-1%", "1.2.3%", "150%"] {
+            assert_eq!(
+                text.parse::<Probability>().unwrap_err(),
+                PsoidError::InvalidRate(text.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_weights() {
+        assert_eq!(
+            DropWeights::new([0; 12]).unwrap_err(),
+            PsoidError::InvalidDropTable("every weight is zero".to_string())
+        );
+        assert!(DropWeights::new([u32::MAX; 12]).is_err());
+    }
+}
@@ -0,0 +1,516 @@
+//! Rare drops by enemy, box area and Section ID, from your own tables
+//!
+//! Each Section ID has its own rare item for every enemy, episode and
+//! difficulty, and its own rare items in each area's boxes. The crate ships
+//! no rare drop data: the tables differ between releases and servers, and no
+//! verified copy is bundled. Load a table you trust from CSV with
+//! [`RareTable::from_csv`] and [`BoxRareTable::from_csv`] and query it. The
+//! CSV needs a header row with the table's `COLUMNS`, and rates are written
+//! as a fraction ("1/2048") or a percentage ("0.05%"). The enemy rares in
+//! the game's own `ItemRT` files can be read with [`RareTable::from_item_rt`].
+//!
+//! # Examples
+//!
+//! The item names here are placeholders for whatever your table lists.
+//!
+//! ```
+//! use psoid::{Difficulty, Guild, RareTable};
+//!
+//! let csv = "episode,difficulty,enemy,guild,item,rate\n\
+//!            1,ultimate,Hildebear,Redria,Item A,1/512\n\
+//!            1,ultimate,Hildebear,Oran,Item B,1/1024\n";
+//! let table = RareTable::from_csv(csv).unwrap();
+//!
+//! let rares = table.rares_for(Guild::Redria, Difficulty::Ultimate);
+//! assert_eq!(rares[0].item, "Item A");
+//!
+//! let sources = table.who_drops("item b");
+//! assert_eq!(sources[0].guild, Guild::Oran);
+//! ```
+//!
+//! ```
+//! use psoid::{Area, BoxRareTable, Difficulty, Guild};
+//!
+//! let csv = "area,difficulty,guild,item,rate\n\
+//!            Ruins 3,very hard,Whitill,Item C,1/2048\n";
+//! let table = BoxRareTable::from_csv(csv).unwrap();
+//!
+//! let rares = table.box_rares(Area::Ruins3, Difficulty::VeryHard, Guild::Whitill);
+//! assert_eq!(rares[0].item, "Item C");
+//! assert_eq!(table.where_boxed("Item C")[0].area, Area::Ruins3);
+//! ```
+
+use crate::roster::csv_records;
+use crate::{
+    Area, Difficulty, Episode, Guild, Probability, PsoidError, RareItemNames, RareItemSet,
+};
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+/// One enemy's rare drop for one Section ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RareDrop {
+    pub episode: Episode,
+    pub difficulty: Difficulty,
+    pub enemy: String,
+    pub guild: Guild,
+    pub item: String,
+    /// The chance the enemy drops the item when killed
+    pub rate: Probability,
+}
+
+impl fmt::Display for RareDrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ({}): {} at {} ({:.4}%)",
+            self.episode,
+            self.difficulty,
+            self.enemy,
+            self.guild.name(),
+            self.item,
+            self.rate,
+            self.rate.percent()
+        )
+    }
+}
+
+/// A rare drop table loaded from your own data, queried by Section ID or by
+/// item
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RareTable {
+    drops: Vec<RareDrop>,
+}
+
+impl RareTable {
+    /// Columns required in a rare table CSV header
+    pub const COLUMNS: [&'static str; 6] =
+        ["episode", "difficulty", "enemy", "guild", "item", "rate"];
+
+    pub fn new(drops: Vec<RareDrop>) -> RareTable {
+        RareTable { drops }
+    }
+
+    /// Read a table from CSV with a header row
+    ///
+    /// Column order doesn't matter and extra columns are ignored. Unlike a
+    /// roster, any bad row fails the whole table, naming the line.
+    pub fn from_csv(input: &str) -> Result<RareTable, PsoidError> {
+        let drops = read_csv(input, Self::COLUMNS, |row| {
+            Ok(RareDrop {
+                episode: row.parse(0)?,
+                difficulty: row.parse(1)?,
+                enemy: row.get(2)?.to_string(),
+                guild: row.parse(3)?,
+                item: row.get(4)?.to_string(),
+                rate: row.parse(5)?,
+            })
+        })?;
+        Ok(RareTable { drops })
+    }
+
+    /// Take the enemy rares from the game's rare item tables
+    ///
+    /// Items and enemies are named with `names`. Empty slots are skipped, and
+    /// drops keep the set's archive order. Box rares aren't included, as the
+    /// tables only give their floor number.
+    pub fn from_item_rt(set: &RareItemSet, names: &RareItemNames) -> RareTable {
+        let drops = set
+            .entries()
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .table
+                    .enemy_rares()
+                    .into_iter()
+                    .map(move |(index, slot)| RareDrop {
+                        episode: entry.episode,
+                        difficulty: entry.difficulty,
+                        enemy: names.enemy(index),
+                        guild: entry.guild,
+                        item: names.item(slot.item),
+                        rate: slot.probability(),
+                    })
+            })
+            .collect();
+        RareTable { drops }
+    }
+
+    /// Get every drop in the table, in the order they were added
+    pub fn drops(&self) -> &[RareDrop] {
+        &self.drops
+    }
+
+    /// Get a Section ID's rare drops at a difficulty
+    ///
+    /// Drops are ordered by episode, then by their order in the table.
+    pub fn rares_for(&self, guild: Guild, difficulty: Difficulty) -> Vec<&RareDrop> {
+        let mut rares: Vec<&RareDrop> = self
+            .drops
+            .iter()
+            .filter(|drop| drop.guild == guild && drop.difficulty == difficulty)
+            .collect();
+        rares.sort_by_key(|drop| drop.episode);
+        rares
+    }
+
+    /// Find every enemy, difficulty and Section ID that drops an item
+    ///
+    /// The item name is matched ignoring case. Sources are ordered by rate,
+    /// best first.
+    pub fn who_drops(&self, item: &str) -> Vec<&RareDrop> {
+        let item = item.trim();
+        let mut sources: Vec<&RareDrop> = self
+            .drops
+            .iter()
+            .filter(|drop| drop.item.eq_ignore_ascii_case(item))
+            .collect();
+        sources.sort_by_key(|drop| Reverse(drop.rate));
+        sources
+    }
+}
+
+/// One area's box rare for one Section ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoxRare {
+    pub area: Area,
+    pub difficulty: Difficulty,
+    pub guild: Guild,
+    pub item: String,
+    /// The chance a box in the area holds the item
+    pub rate: Probability,
+}
+
+impl fmt::Display for BoxRare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} boxes ({}): {} at {} ({:.4}%)",
+            self.area,
+            self.difficulty,
+            self.guild.name(),
+            self.item,
+            self.rate,
+            self.rate.percent()
+        )
+    }
+}
+
+/// A box rare table loaded from your own data, queried by area and Section ID
+/// or by item
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoxRareTable {
+    rares: Vec<BoxRare>,
+}
+
+impl BoxRareTable {
+    /// Columns required in a box rare table CSV header
+    pub const COLUMNS: [&'static str; 5] = ["area", "difficulty", "guild", "item", "rate"];
+
+    pub fn new(rares: Vec<BoxRare>) -> BoxRareTable {
+        BoxRareTable { rares }
+    }
+
+    /// Read a table from CSV with a header row
+    ///
+    /// Column order doesn't matter and extra columns are ignored. Any bad row
+    /// fails the whole table, naming the line.
+    pub fn from_csv(input: &str) -> Result<BoxRareTable, PsoidError> {
+        let rares = read_csv(input, Self::COLUMNS, |row| {
+            Ok(BoxRare {
+                area: row.parse(0)?,
+                difficulty: row.parse(1)?,
+                guild: row.parse(2)?,
+                item: row.get(3)?.to_string(),
+                rate: row.parse(4)?,
+            })
+        })?;
+        Ok(BoxRareTable { rares })
+    }
+
+    /// Get every box rare in the table, in the order they were added
+    pub fn rares(&self) -> &[BoxRare] {
+        &self.rares
+    }
+
+    /// Get the box rares in an area for a Section ID at a difficulty
+    pub fn box_rares(&self, area: Area, difficulty: Difficulty, guild: Guild) -> Vec<&BoxRare> {
+        self.rares
+            .iter()
+            .filter(|rare| {
+                rare.area == area && rare.difficulty == difficulty && rare.guild == guild
+            })
+            .collect()
+    }
+
+    /// Find every area, difficulty and Section ID whose boxes hold an item
+    ///
+    /// The item name is matched ignoring case. Sources are ordered by rate,
+    /// best first, then by area.
+    pub fn where_boxed(&self, item: &str) -> Vec<&BoxRare> {
+        let item = item.trim();
+        let mut sources: Vec<&BoxRare> = self
+            .rares
+            .iter()
+            .filter(|rare| rare.item.eq_ignore_ascii_case(item))
+            .collect();
+        sources.sort_by_key(|rare| (Reverse(rare.rate), rare.area));
+        sources
+    }
+}
+
+/// A data row of a table CSV, with fields in the table's column order
+pub(crate) struct CsvRow<'a> {
+    line: usize,
+    fields: Vec<Option<String>>,
+    columns: &'a [&'static str],
+}
+
+impl CsvRow<'_> {
+    /// Get a required field
+    pub(crate) fn get(&self, index: usize) -> Result<&str, PsoidError> {
+        self.fields[index]
+            .as_deref()
+            .ok_or_else(|| malformed(self.line, format!("missing {}", self.columns[index])))
+    }
+
+    /// Parse a required field, naming the line if it's invalid
+    pub(crate) fn parse<T: FromStr<Err = PsoidError>>(
+        &self,
+        index: usize,
+    ) -> Result<T, PsoidError> {
+        self.get(index)?
+            .parse()
+            .map_err(|error: PsoidError| malformed(self.line, error))
+    }
+
+    /// An error naming the row's line
+    pub(crate) fn error(&self, message: impl fmt::Display) -> PsoidError {
+        malformed(self.line, message)
+    }
+
+    /// Parse a required whole-number field
+    pub(crate) fn number(&self, index: usize) -> Result<u32, PsoidError> {
+        let value = self.get(index)?;
+        value.parse().map_err(|_| {
+            malformed(
+                self.line,
+                format!("{} must be a number, got {}", self.columns[index], value),
+            )
+        })
+    }
+}
+
+/// Read a CSV table whose header names every one of `columns`
+pub(crate) fn read_csv<T, const N: usize>(
+    input: &str,
+    columns: [&'static str; N],
+    build: impl Fn(&CsvRow<'_>) -> Result<T, PsoidError>,
+) -> Result<Vec<T>, PsoidError> {
+    let mut records = csv_records(input).into_iter();
+
+    let Some((header_line, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header = header.map_err(|message| malformed(header_line, message))?;
+    let mut positions = [0; N];
+    for (position, name) in positions.iter_mut().zip(columns) {
+        *position = header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+            .ok_or(PsoidError::MissingColumn(name))?;
+    }
+
+    records
+        .map(|(line, fields)| {
+            let fields = fields.map_err(|message| malformed(line, message))?;
+            let row = CsvRow {
+                line,
+                fields: positions
+                    .iter()
+                    .map(|&position| {
+                        fields
+                            .get(position)
+                            .map(|value| value.trim().to_string())
+                            .filter(|value| !value.is_empty())
+                    })
+                    .collect(),
+                columns: &columns,
+            };
+            build(&row)
+        })
+        .collect()
+}
+
+fn malformed(line: usize, message: impl fmt::Display) -> PsoidError {
+    PsoidError::MalformedRow(format!("line {}: {}", line, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "enemy,guild,episode,difficulty,item,rate,notes\n\
+                         Booma,Viridia,1,normal,Item A,1/256,\n\
+                         Booma,Viridia,2,normal,Item B,0.5%,\n\
+                         Dubchic,Viridia,1,normal,Item C,1/1024,\n\
+                         Booma,Oran,1,normal,Item A,1/128,\n\
+                         Booma,Viridia,1,ultimate,\"Item A, Mk II\",1/64,\n\
+                         \n\
+                         Delsaber,Redria,4,hard,item a,1/512,x\n";
+
+    #[test]
+    fn test_from_csv() {
+        let table = RareTable::from_csv(TABLE).unwrap();
+        assert_eq!(table.drops().len(), 6);
+
+        let first = &table.drops()[0];
+        assert_eq!(first.episode, Episode::I);
+        assert_eq!(first.difficulty, Difficulty::Normal);
+        assert_eq!(first.enemy, "Booma");
+        assert_eq!(first.rate, Probability::new(1, 256).unwrap());
+        assert_eq!(table.drops()[4].item, "Item A, Mk II");
+        assert_eq!(table.drops()[5].episode, Episode::IV);
+        assert_eq!(
+            first.to_string(),
+            "Episode I Normal Booma (Viridia): Item A at 1/256 (0.3906%)"
+        );
+    }
+
+    #[test]
+    fn test_rares_for() {
+        let table = RareTable::from_csv(TABLE).unwrap();
+        let items: Vec<&str> = table
+            .rares_for(Guild::Viridia, Difficulty::Normal)
+            .iter()
+            .map(|drop| drop.item.as_str())
+            .collect();
+        assert_eq!(items, ["Item A", "Item C", "Item B"]);
+
+        assert_eq!(
+            table.rares_for(Guild::Viridia, Difficulty::Ultimate).len(),
+            1
+        );
+        assert!(table.rares_for(Guild::Skyly, Difficulty::Normal).is_empty());
+    }
+
+    #[test]
+    fn test_who_drops() {
+        let table = RareTable::from_csv(TABLE).unwrap();
+        let sources: Vec<(Guild, &str)> = table
+            .who_drops(" ITEM A ")
+            .iter()
+            .map(|drop| (drop.guild, drop.enemy.as_str()))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                (Guild::Oran, "Booma"),
+                (Guild::Viridia, "Booma"),
+                (Guild::Redria, "Delsaber"),
+            ]
+        );
+        assert!(table.who_drops("Item D").is_empty());
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        assert_eq!(RareTable::from_csv("").unwrap(), RareTable::default());
+        assert_eq!(
+            RareTable::from_csv("episode,difficulty,enemy,guild,item\n").unwrap_err(),
+            PsoidError::MissingColumn("rate")
+        );
+        assert_eq!(
+            RareTable::from_csv(
+                "episode,difficulty,enemy,guild,item,rate\n3,normal,Booma,Viridia,X,1/2\n"
+            )
+            .unwrap_err(),
+            PsoidError::MalformedRow("line 2: Unknown episode: 3".to_string())
+        );
+        assert_eq!(
+            RareTable::from_csv(
+                "episode,difficulty,enemy,guild,item,rate\n1,normal,Booma,Viridia,,1/2\n"
+            )
+            .unwrap_err(),
+            PsoidError::MalformedRow("line 2: missing item".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_item_rt() {
+        let set = RareItemSet::from_afs(include_bytes!("../tests/fixtures/ItemRT.afs")).unwrap();
+        let mut names = RareItemNames::default();
+        names.name_item([0x00, 0x01, 0x00], "Saber");
+        names.name_enemy(1, "Hildebear");
+
+        let table = RareTable::from_item_rt(&set, &names);
+        let drop = &table.rares_for(Guild::Viridia, Difficulty::Normal)[0];
+        assert_eq!(
+            drop.to_string(),
+            "Episode I Normal Hildebear (Viridia): Saber at 3/65536 (0.0046%)"
+        );
+        assert_eq!(table.who_drops("saber")[0].guild, Guild::Viridia);
+        // Unnamed enemies and items fall back to their index and code
+        assert_eq!(table.drops().len(), 2);
+        assert_eq!(
+            table.drops()[1].to_string(),
+            "Episode I Normal enemy 5 (Viridia): 000300 at 1/1024 (0.0977%)"
+        );
+    }
+
+    const BOXES: &str = "area,difficulty,guild,item,rate\n\
+                         Forest 1,hard,Skyly,Item A,1/4096\n\
+                         Forest 1,hard,Skyly,Item B,1/2048\n\
+                         Caves 2,hard,Skyly,Item A,1/1024\n\
+                         Forest 1,hard,Pinkal,Item C,1/4096\n\
+                         temple alpha,ultimate,Skyly,item a,1/1024\n";
+
+    #[test]
+    fn test_box_rares() {
+        let table = BoxRareTable::from_csv(BOXES).unwrap();
+        assert_eq!(table.rares().len(), 5);
+        assert_eq!(table.rares()[4].area, Area::TempleAlpha);
+
+        let items: Vec<&str> = table
+            .box_rares(Area::Forest1, Difficulty::Hard, Guild::Skyly)
+            .iter()
+            .map(|rare| rare.item.as_str())
+            .collect();
+        assert_eq!(items, ["Item A", "Item B"]);
+        assert!(table
+            .box_rares(Area::Forest1, Difficulty::Normal, Guild::Skyly)
+            .is_empty());
+
+        let sources: Vec<(Area, Difficulty)> = table
+            .where_boxed("ITEM A")
+            .iter()
+            .map(|rare| (rare.area, rare.difficulty))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                (Area::Caves2, Difficulty::Hard),
+                (Area::TempleAlpha, Difficulty::Ultimate),
+                (Area::Forest1, Difficulty::Hard),
+            ]
+        );
+        assert_eq!(
+            table.rares()[0].to_string(),
+            "Forest 1 Hard boxes (Skyly): Item A at 1/4096 (0.0244%)"
+        );
+    }
+
+    #[test]
+    fn test_box_rares_errors() {
+        assert_eq!(
+            BoxRareTable::from_csv("area,guild,item,rate\n").unwrap_err(),
+            PsoidError::MissingColumn("difficulty")
+        );
+        assert_eq!(
+            BoxRareTable::from_csv("area,difficulty,guild,item,rate\nForest 3,hard,Skyly,X,1/2\n")
+                .unwrap_err(),
+            PsoidError::MalformedRow("line 2: Unknown area: Forest 3".to_string())
+        );
+    }
+}
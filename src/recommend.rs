@@ -0,0 +1,174 @@
+//! Rank guilds by a weighted weapon wishlist
+//!
+//! Each guild scores the sum of `rate * weight` over the wishlist, so a
+//! weapon with weight 3 counts three times as much as one with weight 1.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{rank_guilds, Guild, WeaponType};
+//!
+//! let ranking = rank_guilds(&[(WeaponType::Partisans, 3), (WeaponType::Slicers, 1)]);
+//! assert_eq!(ranking[0].guild, Guild::Bluefull);
+//! assert_eq!(ranking[0].score, 13 * 3 + 6);
+//! ```
+
+use crate::{DropRates, Guild, WeaponType};
+use std::cmp::Reverse;
+use std::fmt;
+
+/// How much one wishlist entry adds to a guild's score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeaponScore {
+    pub weapon: WeaponType,
+    pub weight: u32,
+    /// The guild's drop rate for `weapon`, in percent
+    pub rate: u32,
+    /// `rate * weight`, which can't overflow even at the largest weight
+    pub points: u64,
+}
+
+/// A guild's total score for a wishlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildScore {
+    pub guild: Guild,
+    pub score: u64,
+    /// One entry per wishlist entry, in wishlist order
+    pub breakdown: Vec<WeaponScore>,
+}
+
+/// Score every guild against a wishlist of weapon types and weights
+///
+/// Guilds are returned best first; guilds with the same score stay in ID
+/// order. A weapon listed twice counts twice.
+pub fn rank_guilds(wishlist: &[(WeaponType, u32)]) -> Vec<GuildScore> {
+    rank_by(wishlist, |guild| guild.drop_rates())
+}
+
+/// [`rank_guilds`] with each guild's rates from `rates`
+pub(crate) fn rank_by(
+    wishlist: &[(WeaponType, u32)],
+    rates: impl Fn(Guild) -> DropRates,
+) -> Vec<GuildScore> {
+    let mut ranking: Vec<GuildScore> = Guild::ALL
+        .into_iter()
+        .map(|guild| {
+            let rates = rates(guild);
+            let breakdown: Vec<WeaponScore> = wishlist
+                .iter()
+                .map(|&(weapon, weight)| WeaponScore {
+                    weapon,
+                    weight,
+                    rate: rates[weapon],
+                    points: u64::from(rates[weapon]) * u64::from(weight),
+                })
+                .collect();
+            GuildScore {
+                guild,
+                score: breakdown.iter().map(|entry| entry.points).sum(),
+                breakdown,
+            }
+        })
+        .collect();
+
+    ranking.sort_by_key(|entry| Reverse(entry.score));
+    ranking
+}
+
+impl fmt::Display for WeaponScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}% x {} = {}",
+            self.weapon, self.rate, self.weight, self.points
+        )
+    }
+}
+
+impl fmt::Display for GuildScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let breakdown: Vec<String> = self
+            .breakdown
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect();
+        write!(
+            f,
+            "{:<10} {:>4}  ({})",
+            self.guild.name(),
+            self.score,
+            breakdown.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_guilds() {
+        let ranking = rank_guilds(&[(WeaponType::Partisans, 3), (WeaponType::Slicers, 1)]);
+        let order: Vec<(Guild, u64)> = ranking
+            .iter()
+            .map(|entry| (entry.guild, entry.score))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (Guild::Bluefull, 45),
+                (Guild::Pinkal, 36),
+                (Guild::Viridia, 31),
+                (Guild::Redria, 31),
+                (Guild::Whitill, 31),
+                (Guild::Yellowboze, 28),
+                (Guild::Oran, 27),
+                (Guild::Greennill, 24),
+                (Guild::Skyly, 24),
+                (Guild::Purplenum, 15),
+            ]
+        );
+
+        assert_eq!(
+            ranking[0].breakdown,
+            vec![
+                WeaponScore {
+                    weapon: WeaponType::Partisans,
+                    weight: 3,
+                    rate: 13,
+                    points: 39
+                },
+                WeaponScore {
+                    weapon: WeaponType::Slicers,
+                    weight: 1,
+                    rate: 6,
+                    points: 6
+                },
+            ]
+        );
+        assert_eq!(
+            ranking[0].to_string(),
+            "Bluefull     45  (Partisans 13% x 3 = 39, Slicers 6% x 1 = 6)"
+        );
+    }
+
+    #[test]
+    fn test_rank_guilds_empty_wishlist() {
+        let ranking = rank_guilds(&[]);
+        assert_eq!(ranking.len(), 10);
+        assert!(ranking.iter().all(|entry| entry.score == 0));
+        let guilds: Vec<Guild> = ranking.iter().map(|entry| entry.guild).collect();
+        assert_eq!(guilds, Guild::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_rank_guilds_large_weights() {
+        let ranking = rank_guilds(&[
+            (WeaponType::Partisans, u32::MAX),
+            (WeaponType::Slicers, u32::MAX),
+        ]);
+        assert_eq!(ranking[0].guild, Guild::Bluefull);
+        assert_eq!(ranking[0].breakdown[0].points, 13 * u64::from(u32::MAX));
+        assert_eq!(ranking[0].score, 19 * u64::from(u32::MAX));
+    }
+}
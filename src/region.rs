@@ -0,0 +1,213 @@
+//! Release regions
+//!
+//! The Japanese V1, V2 and GameCube releases allow kana names, stored and
+//! summed as Shift-JIS bytes. Blue Burst names are UTF-16 in every region,
+//! but only ASCII ones are supported; Japanese Blue Burst names are deferred
+//! until the client's value table is available.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{calculate, calculate_with, GameVersion, Region};
+//!
+//! let algorithm = Region::Japan.algorithm(GameVersion::V2);
+//! assert_eq!(algorithm.section_id("リコ", None).unwrap().name(), "Skyly");
+//! assert_eq!(calculate_with("リコ", algorithm, None).unwrap().name(), "Skyly");
+//!
+//! // The built-in algorithms are the international ones
+//! assert!(calculate("リコ", GameVersion::V2, None).is_err());
+//!
+//! let algorithms = Region::Japan.algorithms();
+//! assert_eq!(algorithms.get(GameVersion::GameCube).name(), "shift-jis");
+//! ```
+
+use crate::{
+    normalize, Algorithms, AsciiAlgorithm, BlueBurstAlgorithm, GameVersion, PsoidError,
+    SectionIdAlgorithm, ShiftJisAlgorithm,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// The region a copy of the game was released in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// The North American and European releases
+    International,
+    Japan,
+}
+
+impl Region {
+    /// Every region
+    pub const ALL: [Region; 2] = [Region::International, Region::Japan];
+
+    /// Get the name of the region as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::International => "International",
+            Region::Japan => "Japan",
+        }
+    }
+
+    /// Get the algorithm the region's release of a version uses
+    pub fn algorithm(&self, version: GameVersion) -> &'static dyn SectionIdAlgorithm {
+        match (self, version) {
+            (_, GameVersion::BlueBurst) => &BlueBurstAlgorithm,
+            (Region::International, _) => &AsciiAlgorithm,
+            (Region::Japan, _) => &ShiftJisAlgorithm,
+        }
+    }
+
+    /// Get the algorithm the region's release of every version uses
+    pub fn algorithms(&self) -> Algorithms<'static> {
+        GameVersion::ALL
+            .into_iter()
+            .fold(Algorithms::builtin(), |algorithms, version| {
+                algorithms.with(version, self.algorithm(version))
+            })
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a region, ignoring case, spaces, dashes and underscores
+///
+/// Accepts "international"/"intl"/"us"/"eu" and "japan"/"jp"/"ja".
+impl FromStr for Region {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match normalize(value).as_str() {
+            "international" | "intl" | "us" | "na" | "eu" | "pal" => Ok(Region::International),
+            "japan" | "japanese" | "jp" | "ja" | "jpn" => Ok(Region::Japan),
+            _ => Err(PsoidError::UnknownRegion(value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{find_names_with, CharacterClass, Guild, SearchOptions};
+
+    #[test]
+    fn test_parse_region() {
+        for region in Region::ALL {
+            assert_eq!(region.name().parse::<Region>().unwrap(), region);
+        }
+        assert_eq!("JP".parse::<Region>().unwrap(), Region::Japan);
+        assert_eq!("us".parse::<Region>().unwrap(), Region::International);
+        assert_eq!(
+            "korea".parse::<Region>().unwrap_err(),
+            PsoidError::UnknownRegion("korea".to_string())
+        );
+    }
+
+    #[test]
+    fn test_japanese_names() {
+        // Sums of each name's Shift-JIS bytes
+        let cases = [
+            ("リコ", Guild::Skyly),
+            ("はなこ", Guild::Skyly),
+            ("ｶﾅ", Guild::Whitill),
+            ("Ｒｉｃｏ", Guild::Purplenum),
+            ("ユキ ー", Guild::Purplenum),
+        ];
+        for version in [GameVersion::V1, GameVersion::V2, GameVersion::GameCube] {
+            let algorithm = Region::Japan.algorithm(version);
+            for (name, expected) in cases {
+                assert_eq!(
+                    algorithm.section_id(name, None).unwrap(),
+                    expected,
+                    "{}",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_name_length_counts_shift_jis_bytes() {
+        let algorithm = Region::Japan.algorithm(GameVersion::V2);
+        assert!(algorithm.section_id("あいうえおか", None).is_ok());
+        assert!(algorithm.section_id("ｱｲｳｴｵｶｷｸｹｺｻｼ", None).is_ok());
+        assert!(algorithm.section_id("Rico ﾘｺ リコ", None).is_ok());
+        assert_eq!(
+            algorithm.section_id("あいうえおかき", None).unwrap_err(),
+            PsoidError::NameTooLong { len: 14, max: 12 }
+        );
+        assert_eq!(
+            algorithm
+                .section_id("あいうえおかきくけこさし", None)
+                .unwrap_err(),
+            PsoidError::NameTooLong { len: 24, max: 12 }
+        );
+    }
+
+    #[test]
+    fn test_search_respects_shift_jis_length() {
+        let algorithm = Region::Japan.algorithm(GameVersion::V2);
+        let options = SearchOptions {
+            prefix: "リコ".to_string(),
+            charset: vec!['あ', 'い', 'a'],
+            ..SearchOptions::default()
+        };
+        let names: Vec<String> = find_names_with(Guild::Skyly, algorithm, None, &options)
+            .unwrap()
+            .collect();
+        assert!(!names.is_empty());
+        for name in names {
+            assert_eq!(
+                algorithm.section_id(&name, None),
+                Ok(Guild::Skyly),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_ascii_matches_international() {
+        for name in ["Bob", "Testing", "PSO Player"] {
+            assert_eq!(
+                Region::Japan
+                    .algorithm(GameVersion::V2)
+                    .section_id(name, None),
+                Region::International
+                    .algorithm(GameVersion::V2)
+                    .section_id(name, None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_blueburst_is_the_same_everywhere() {
+        let class = Some(CharacterClass::FOmarl);
+        assert_eq!(
+            Region::Japan
+                .algorithm(GameVersion::BlueBurst)
+                .section_id("Hanako", class),
+            Region::International
+                .algorithm(GameVersion::BlueBurst)
+                .section_id("Hanako", class)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_character() {
+        let algorithm = Region::Japan.algorithm(GameVersion::V1);
+        assert_eq!(
+            algorithm.section_id("リコ号", None).unwrap_err(),
+            PsoidError::UnsupportedCharacter {
+                index: 2, ch: '号'
+            }
+        );
+        assert_eq!(
+            algorithm.section_id("café", None).unwrap_err(),
+            PsoidError::UnsupportedCharacter { index: 3, ch: 'é' }
+        );
+    }
+}
@@ -0,0 +1,655 @@
+//! Structured reports and machine-readable output
+//!
+//! Every output format is rendered from the same report struct, so the text
+//! shown by `Display` and the JSON/CSV/TSV output always carry the same data.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Guild, OutputFormat};
+//!
+//! let report = Guild::Redria.report();
+//! assert_eq!(report.mag_type, "C");
+//!
+//! let json = report.render(OutputFormat::Json);
+//! assert!(json.starts_with(r#"{"id":6,"name":"Redria""#));
+//! ```
+
+use crate::{
+    calculate, CharacterClass, Comparison, Difficulty, DropRates, GameVersion, Guild, GuildInfo,
+    PsoidError, WeaponDrop,
+};
+use std::fmt;
+
+/// Output formats supported by reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text, the same as `Display`
+    Text,
+    /// One JSON object per report
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+/// A weapon drop with its percentage
+///
+/// When every weapon drops equally, `weapon` is "All Equal" and `percent` is
+/// `None` (`null` in JSON, an empty cell in CSV/TSV).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DropSummary {
+    pub weapon: &'static str,
+    pub percent: Option<u32>,
+}
+
+/// Everything the crate knows about a guild, bundled for output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GuildReport {
+    pub id: u32,
+    pub name: &'static str,
+    /// The difficulty the drop data is for, or `None` for the published table
+    pub difficulty: Option<Difficulty>,
+    pub best_class: &'static str,
+    pub common_drop: DropSummary,
+    pub rare_drop: DropSummary,
+    pub mag_type: &'static str,
+    pub drop_rates: DropRates,
+}
+
+/// A character name together with the guild it calculates to
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NameReport {
+    pub name: String,
+    pub version: GameVersion,
+    pub class: Option<CharacterClass>,
+    pub guild: GuildReport,
+}
+
+/// A single value in a report row
+enum Value {
+    Text(String),
+    Number(u32),
+    Null,
+}
+
+impl Guild {
+    /// Bundle all of this guild's data into a report, using the published
+    /// drop table
+    ///
+    /// The same as reporting a [`GuildInfo`] with [`Guild::drop_weights`].
+    pub fn report(&self) -> GuildReport {
+        GuildInfo::new(*self, GameVersion::V1, self.drop_weights()).report()
+    }
+}
+
+impl GuildInfo {
+    /// Bundle all of this guild's data into a report, using this version and
+    /// difficulty's drop table
+    ///
+    /// The built-in weights are the published table, which isn't specific to
+    /// a difficulty, so the report's difficulty is `None` for them.
+    pub fn report(&self) -> GuildReport {
+        GuildReport::new(
+            self.guild,
+            Some(self.difficulty).filter(|_| !self.is_published()),
+            self.common_drop(),
+            self.rare_drop(),
+            self.drop_rates(),
+        )
+    }
+}
+
+impl From<WeaponDrop> for DropSummary {
+    fn from(drop: WeaponDrop) -> Self {
+        DropSummary {
+            weapon: drop.name(),
+            percent: drop.percent(),
+        }
+    }
+}
+
+impl From<Guild> for GuildReport {
+    fn from(guild: Guild) -> Self {
+        guild.report()
+    }
+}
+
+impl GuildReport {
+    fn new(
+        guild: Guild,
+        difficulty: Option<Difficulty>,
+        common_drop: WeaponDrop,
+        rare_drop: WeaponDrop,
+        drop_rates: DropRates,
+    ) -> GuildReport {
+        GuildReport {
+            id: guild.id(),
+            name: guild.name(),
+            difficulty,
+            best_class: guild.best_class(),
+            common_drop: common_drop.into(),
+            rare_drop: rare_drop.into(),
+            mag_type: guild.mag_type(),
+            drop_rates,
+        }
+    }
+
+    /// Column names used for CSV/TSV output
+    pub const COLUMNS: [&'static str; 21] = [
+        "id",
+        "guild",
+        "difficulty",
+        "best_class",
+        "common_drop",
+        "common_drop_percent",
+        "rare_drop",
+        "rare_drop_percent",
+        "mag_type",
+        "sabers",
+        "swords",
+        "daggers",
+        "partisans",
+        "slicers",
+        "handguns",
+        "rifles",
+        "machineguns",
+        "shotguns",
+        "canes",
+        "rods",
+        "wands",
+    ];
+
+    /// Render this report in the given format
+    ///
+    /// CSV and TSV output include a header row.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_string(),
+            OutputFormat::Json => self.json(),
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                render_table(format, &Self::COLUMNS, &[self.values()])
+            }
+        }
+    }
+
+    /// Row values in the same order as [`GuildReport::COLUMNS`]
+    fn values(&self) -> Vec<Value> {
+        let mut values = vec![
+            Value::Number(self.id),
+            Value::Text(self.name.to_string()),
+            self.difficulty.map_or(Value::Null, |difficulty| {
+                Value::Text(difficulty.name().to_string())
+            }),
+            Value::Text(self.best_class.to_string()),
+            Value::Text(self.common_drop.weapon.to_string()),
+            self.common_drop.percent.map_or(Value::Null, Value::Number),
+            Value::Text(self.rare_drop.weapon.to_string()),
+            self.rare_drop.percent.map_or(Value::Null, Value::Number),
+            Value::Text(self.mag_type.to_string()),
+        ];
+        values.extend(self.drop_rates.iter().map(|(_, rate)| Value::Number(rate)));
+        values
+    }
+
+    fn json(&self) -> String {
+        let rates: Vec<String> = self
+            .drop_rates
+            .iter()
+            .map(|(weapon, rate)| {
+                format!("{}:{}", json_string(&weapon.name().to_lowercase()), rate)
+            })
+            .collect();
+
+        format!(
+            "{{\"id\":{},\"name\":{},\"difficulty\":{},\"best_class\":{},\"common_drop\":{},\"rare_drop\":{},\"mag_type\":{},\"drop_rates\":{{{}}}}}",
+            self.id,
+            json_string(self.name),
+            self.difficulty
+                .map_or("null".to_string(), |difficulty| json_string(difficulty.name())),
+            json_string(self.best_class),
+            self.common_drop.json(),
+            self.rare_drop.json(),
+            json_string(self.mag_type),
+            rates.join(",")
+        )
+    }
+}
+
+impl fmt::Display for GuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guild: {}, ", self.name)?;
+        if let Some(difficulty) = self.difficulty {
+            write!(f, "Difficulty: {}, ", difficulty)?;
+        }
+        write!(
+            f,
+            "Class: {}, Common: {}, Rare: {}, MAG: {}",
+            self.best_class, self.common_drop, self.rare_drop, self.mag_type
+        )
+    }
+}
+
+impl fmt::Display for DropSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percent {
+            Some(percent) => write!(f, "{} ({}%)", self.weapon, percent),
+            None => f.write_str(self.weapon),
+        }
+    }
+}
+
+impl DropSummary {
+    fn json(self) -> String {
+        format!(
+            "{{\"weapon\":{},\"percent\":{}}}",
+            json_string(self.weapon),
+            self.percent
+                .map_or("null".to_string(), |percent| percent.to_string())
+        )
+    }
+}
+
+impl NameReport {
+    /// Calculate the guild for a name and bundle it into a report
+    pub fn new(
+        name: &str,
+        version: GameVersion,
+        class: Option<CharacterClass>,
+    ) -> Result<NameReport, PsoidError> {
+        let guild = calculate(name, version, class)?;
+        let info = GuildInfo::new(guild, version, guild.drop_weights());
+        Ok(NameReport::from_info(name, class, &info))
+    }
+
+    /// Report a name whose guild data has already been looked up
+    pub(crate) fn from_info(
+        name: &str,
+        class: Option<CharacterClass>,
+        info: &GuildInfo,
+    ) -> NameReport {
+        NameReport {
+            name: name.to_string(),
+            version: info.version,
+            class,
+            guild: info.report(),
+        }
+    }
+
+    /// Column names used for CSV/TSV output
+    pub fn columns() -> Vec<&'static str> {
+        let mut columns = vec!["name", "version", "class"];
+        columns.extend(GuildReport::COLUMNS);
+        columns
+    }
+
+    /// Render this report in the given format
+    ///
+    /// CSV and TSV output include a header row.
+    pub fn render(&self, format: OutputFormat) -> String {
+        render_names(format, std::slice::from_ref(self))
+    }
+
+    fn values(&self) -> Vec<Value> {
+        let mut values = vec![
+            Value::Text(self.name.clone()),
+            Value::Text(self.version.name().to_string()),
+            self.class
+                .map_or(Value::Null, |class| Value::Text(class.name().to_string())),
+        ];
+        values.extend(self.guild.values());
+        values
+    }
+
+    fn json(&self) -> String {
+        format!(
+            "{{\"name\":{},\"version\":{},\"class\":{},\"guild\":{}}}",
+            json_string(&self.name),
+            json_string(self.version.name()),
+            self.class
+                .map_or("null".to_string(), |class| json_string(class.name())),
+            self.guild.json()
+        )
+    }
+}
+
+impl fmt::Display for NameReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.name, self.version.name())?;
+        if let Some(class) = self.class {
+            write!(f, ", {}", class.name())?;
+        }
+        write!(f, "): {}", self.guild)
+    }
+}
+
+/// Render several name reports as one document
+///
+/// Text output has one report per line, JSON output is one object per line
+/// (JSON Lines) and CSV/TSV output share a single header row.
+pub fn render_names(format: OutputFormat, reports: &[NameReport]) -> String {
+    match format {
+        OutputFormat::Text => lines(reports.iter().map(|report| report.to_string())),
+        OutputFormat::Json => lines(reports.iter().map(|report| report.json())),
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let rows: Vec<Vec<Value>> = reports.iter().map(|report| report.values()).collect();
+            render_table(format, &NameReport::columns(), &rows)
+        }
+    }
+}
+
+impl Comparison {
+    /// Column names used for CSV/TSV output
+    pub const COLUMNS: [&'static str; 10] = [
+        "name",
+        "version",
+        "class",
+        "guild",
+        "error",
+        "differs",
+        "common_drop",
+        "common_drop_percent",
+        "rare_drop",
+        "rare_drop_percent",
+    ];
+
+    /// Render every entry in the given format
+    ///
+    /// Each entry has either a guild or an error. Entries without a report
+    /// have `null` drops in JSON and empty drop cells in CSV/TSV. JSON output
+    /// is one object per entry (JSON Lines) and CSV/TSV output has a header
+    /// row.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_string(),
+            OutputFormat::Json => lines(self.entries.iter().map(|entry| {
+                let (guild, error) = match &entry.result {
+                    Ok(guild) => (json_string(guild.name()), "null".to_string()),
+                    Err(error) => ("null".to_string(), json_string(&error.to_string())),
+                };
+                let (common, rare) = match &entry.report {
+                    Some(report) => (report.common_drop.json(), report.rare_drop.json()),
+                    None => ("null".to_string(), "null".to_string()),
+                };
+                format!(
+                    "{{\"name\":{},\"version\":{},\"class\":{},\"guild\":{},\"error\":{},\"differs\":{},\"common_drop\":{},\"rare_drop\":{}}}",
+                    json_string(&self.name),
+                    json_string(entry.version.name()),
+                    entry
+                        .class
+                        .map_or("null".to_string(), |class| json_string(class.name())),
+                    guild,
+                    error,
+                    entry.differs,
+                    common,
+                    rare
+                )
+            })),
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let rows: Vec<Vec<Value>> = self
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        let text = |text: &str| Value::Text(text.to_string());
+                        let (guild, error) = match &entry.result {
+                            Ok(guild) => (text(guild.name()), Value::Null),
+                            Err(error) => (Value::Null, text(&error.to_string())),
+                        };
+                        let mut row = vec![
+                            text(&self.name),
+                            text(entry.version.name()),
+                            entry.class.map_or(Value::Null, |class| text(class.name())),
+                            guild,
+                            error,
+                            text(if entry.differs { "true" } else { "false" }),
+                        ];
+                        match &entry.report {
+                            Some(report) => {
+                                for drop in [report.common_drop, report.rare_drop] {
+                                    row.push(text(drop.weapon));
+                                    row.push(drop.percent.map_or(Value::Null, Value::Number));
+                                }
+                            }
+                            None => row.extend((0..4).map(|_| Value::Null)),
+                        }
+                        row
+                    })
+                    .collect();
+                render_table(format, &Self::COLUMNS, &rows)
+            }
+        }
+    }
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> String {
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+fn render_table(format: OutputFormat, columns: &[&str], rows: &[Vec<Value>]) -> String {
+    let delimiter = if format == OutputFormat::Tsv {
+        "\t"
+    } else {
+        ","
+    };
+    let header = columns
+        .iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<_>>()
+        .join(delimiter);
+
+    let rows = rows.iter().map(|row| {
+        row.iter()
+            .map(|value| match value {
+                Value::Text(text) => delimited_field(format, text),
+                Value::Number(number) => number.to_string(),
+                Value::Null => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join(delimiter)
+    });
+
+    lines(std::iter::once(header).chain(rows))
+}
+
+/// Escape a field for CSV (RFC 4180 quoting) or TSV (tabs and newlines become spaces)
+fn delimited_field(format: OutputFormat, text: &str) -> String {
+    if format == OutputFormat::Tsv {
+        return text.replace(['\t', '\n', '\r'], " ");
+    }
+
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_matches_guild() {
+        let report = Guild::Purplenum.report();
+        assert_eq!(report.id, 4);
+        assert_eq!(report.name, "Purplenum");
+        assert_eq!(report.common_drop.weapon, "Machineguns");
+        assert_eq!(report.rare_drop.percent, Some(10));
+        assert_eq!(report.drop_rates, Guild::Purplenum.drop_rates());
+        assert_eq!(
+            report.render(OutputFormat::Text),
+            Guild::Purplenum.to_string()
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = Guild::Yellowboze.report().render(OutputFormat::Json);
+        assert_eq!(
+            json,
+            "{\"id\":8,\"name\":\"Yellowboze\",\"difficulty\":null,\"best_class\":\"Ranger\",\
+             \"common_drop\":{\"weapon\":\"All Equal\",\"percent\":null},\
+             \"rare_drop\":{\"weapon\":\"All Equal\",\"percent\":null},\"mag_type\":\"C\",\
+             \"drop_rates\":{\"sabers\":13,\"swords\":7,\"daggers\":7,\"partisans\":7,\
+             \"slicers\":7,\"handguns\":13,\"rifles\":7,\"machineguns\":7,\"shotguns\":5,\
+             \"canes\":13,\"rods\":7,\"wands\":7}}"
+        );
+    }
+
+    #[test]
+    fn test_render_csv_and_tsv() {
+        let csv = Guild::Viridia.report().render(OutputFormat::Csv);
+        let mut rows = csv.lines();
+        assert_eq!(rows.next().unwrap(), GuildReport::COLUMNS.join(","));
+        assert_eq!(
+            rows.next().unwrap(),
+            "0,Viridia,,Ranger,Partisans,10,Slicers,1,A,13,6,7,10,1,13,6,6,11,13,7,7"
+        );
+        assert!(rows.next().is_none());
+
+        let tsv = Guild::Viridia.report().render(OutputFormat::Tsv);
+        assert_eq!(tsv, csv.replace(',', "\t"));
+    }
+
+    #[test]
+    fn test_render_names() {
+        let reports = vec![
+            NameReport::new("Bob", GameVersion::V1, None).unwrap(),
+            NameReport::new(
+                "Big, \"Guns\"",
+                GameVersion::BlueBurst,
+                Some(CharacterClass::RAmar),
+            )
+            .unwrap(),
+        ];
+
+        let csv = render_names(OutputFormat::Csv, &reports);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("name,version,class,id,guild,"));
+        assert!(rows[1].starts_with("Bob,V1,,5,Pinkal,,Force,"));
+        assert!(rows[2].starts_with("\"Big, \"\"Guns\"\"\",BlueBurst,RAmar,"));
+
+        let json = render_names(OutputFormat::Json, &reports);
+        let rows: Vec<&str> = json.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("{\"name\":\"Bob\",\"version\":\"V1\",\"class\":null,"));
+        assert!(rows[1].starts_with(
+            "{\"name\":\"Big, \\\"Guns\\\"\",\"version\":\"BlueBurst\",\"class\":\"RAmar\","
+        ));
+
+        let text = render_names(OutputFormat::Text, &reports);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            "Bob (V1): Guild: Pinkal, Class: Force, \
+             Common: Wands (13%), Rare: Rifles (1%), MAG: B"
+        );
+    }
+
+    #[test]
+    fn test_render_comparison() {
+        let comparison = crate::compare("Bob\t").unwrap();
+
+        let json = comparison.render(OutputFormat::Json);
+        let rows: Vec<&str> = json.lines().collect();
+        assert_eq!(rows.len(), 16);
+        assert_eq!(
+            rows[2],
+            r#"{"name":"Bob\t","version":"GameCube","class":null,"guild":"Purplenum","error":null,"differs":false,"common_drop":{"weapon":"Machineguns","percent":13},"rare_drop":{"weapon":"Daggers","percent":10}}"#
+        );
+        assert_eq!(
+            rows[3],
+            r#"{"name":"Bob\t","version":"BlueBurst","class":null,"guild":null,"error":"Unsupported character: \t (at position 3)","differs":false,"common_drop":null,"rare_drop":null}"#
+        );
+
+        let csv = compare_csv("Bob");
+        assert_eq!(
+            csv[0],
+            "name,version,class,guild,error,differs,\
+             common_drop,common_drop_percent,rare_drop,rare_drop_percent"
+        );
+        assert_eq!(csv[1], "Bob,V1,,Pinkal,,false,Wands,13,Rifles,1");
+        assert_eq!(
+            csv[5],
+            "Bob,BlueBurst,HUmar,Viridia,,true,Partisans,10,Slicers,1"
+        );
+    }
+
+    fn compare_csv(name: &str) -> Vec<String> {
+        crate::compare(name)
+            .unwrap()
+            .render(OutputFormat::Csv)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_guild_and_info_reports_agree() {
+        for guild in Guild::ALL {
+            for version in GameVersion::ALL {
+                let info = GuildInfo::new(guild, version, guild.drop_weights());
+                assert_eq!(guild.report(), info.report(), "{}", guild);
+            }
+        }
+    }
+
+    #[test]
+    fn test_yellowboze_reports_all_equal() {
+        let report = NameReport::new("Tank", GameVersion::V1, None).unwrap();
+        assert_eq!(report.guild.name, "Yellowboze");
+        assert_eq!(
+            report.render(OutputFormat::Text),
+            "Tank (V1): Guild: Yellowboze, Class: Ranger, \
+             Common: All Equal, Rare: All Equal, MAG: C"
+        );
+        let csv = report.render(OutputFormat::Csv);
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("Tank,V1,,8,Yellowboze,,Ranger,All Equal,,All Equal,,C,"));
+
+        for version in GameVersion::ALL {
+            let info = GuildInfo::new(Guild::Yellowboze, version, Guild::Yellowboze.drop_weights());
+            let report = info.report();
+            assert_eq!(report.common_drop.to_string(), "All Equal");
+            assert_eq!(report.rare_drop.to_string(), "All Equal");
+        }
+
+        let comparison = crate::compare("Tank").unwrap();
+        assert!(comparison.to_string().starts_with(
+            "V1         -          Yellowboze (Common: All Equal, Rare: All Equal)\n"
+        ));
+    }
+
+    #[test]
+    fn test_name_report_error() {
+        assert_eq!(
+            NameReport::new("", GameVersion::V1, None).unwrap_err(),
+            PsoidError::EmptyName
+        );
+    }
+}
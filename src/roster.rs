@@ -0,0 +1,672 @@
+//! Batch processing of character rosters
+//!
+//! A roster is a CSV file with a header row, or a JSON Lines file with one
+//! object per character. Each row has a `name`, and optionally a `version`
+//! and `class`, which are parsed the same way as `FromStr`. Rows that fail to
+//! parse or calculate are collected as errors instead of stopping the batch.
+//!
+//! JSON Lines rosters are read by a built-in parser for flat objects, whether
+//! or not the `json` feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{process_roster, GameVersion, Guild, RosterFormat};
+//!
+//! let csv = "name,version,class\nBob,v1,\nPSO Player,bb,RAmar\nBad\tName,bb,\n";
+//! let roster = process_roster(csv, RosterFormat::Csv, GameVersion::V1).unwrap();
+//!
+//! assert_eq!(roster.reports.len(), 2);
+//! assert_eq!(roster.reports[1].guild.name, "Bluefull");
+//! assert_eq!(roster.errors[0].line, 4);
+//! ```
+
+use crate::{
+    Algorithms, CharacterClass, Difficulty, DropTableSet, GameVersion, NameReport, PsoidError,
+};
+
+/// File formats accepted by [`process_roster`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RosterFormat {
+    /// Comma-separated values with a header row naming the columns
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// A roster row that could not be processed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RosterError {
+    /// Line number in the roster (starting at 1)
+    pub line: usize,
+    /// The character name, if the row had one
+    pub name: Option<String>,
+    pub error: PsoidError,
+}
+
+/// The results of processing every row in a roster
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Roster {
+    /// Reports for the rows that calculated, in roster order
+    pub reports: Vec<NameReport>,
+    /// Rows that were skipped, in roster order
+    pub errors: Vec<RosterError>,
+}
+
+/// The raw columns of a single roster row
+struct Row {
+    line: usize,
+    name: Option<String>,
+    version: Option<String>,
+    class: Option<String>,
+}
+
+/// Calculate the Section ID for every character in a roster
+///
+/// Rows without a version use `default_version`. Blank lines are skipped.
+/// Only problems with the roster as a whole, such as a CSV header without a
+/// `name` column, are returned as an error; everything else is collected in
+/// [`Roster::errors`]. Drops are reported from the built-in tables on
+/// Normal; use [`DropTableSet::process_roster`] for others.
+pub fn process_roster(
+    input: &str,
+    format: RosterFormat,
+    default_version: GameVersion,
+) -> Result<Roster, PsoidError> {
+    DropTableSet::default().process_roster(input, format, default_version, Difficulty::Normal)
+}
+
+impl DropTableSet {
+    /// Like [`process_roster`], reporting drops from this set's tables for
+    /// `difficulty`
+    ///
+    /// Rows whose version doesn't have the difficulty are collected as errors.
+    pub fn process_roster(
+        &self,
+        input: &str,
+        format: RosterFormat,
+        default_version: GameVersion,
+        difficulty: Difficulty,
+    ) -> Result<Roster, PsoidError> {
+        let algorithms = Algorithms::builtin();
+        self.process_roster_with(input, format, default_version, difficulty, &algorithms)
+    }
+
+    /// Like [`DropTableSet::process_roster`], calculating each row with its
+    /// version's algorithm from `algorithms`
+    pub fn process_roster_with(
+        &self,
+        input: &str,
+        format: RosterFormat,
+        default_version: GameVersion,
+        difficulty: Difficulty,
+        algorithms: &Algorithms,
+    ) -> Result<Roster, PsoidError> {
+        let rows = match format {
+            RosterFormat::Csv => csv_rows(input)?,
+            RosterFormat::JsonLines => json_rows(input),
+        };
+
+        let mut roster = Roster::default();
+        for row in rows {
+            match row.and_then(|row| self.process_row(row, default_version, difficulty, algorithms))
+            {
+                Ok(report) => roster.reports.push(report),
+                Err(error) => roster.errors.push(error),
+            }
+        }
+
+        Ok(roster)
+    }
+
+    fn process_row(
+        &self,
+        row: Row,
+        default_version: GameVersion,
+        difficulty: Difficulty,
+        algorithms: &Algorithms,
+    ) -> Result<NameReport, RosterError> {
+        let fail = |error| RosterError {
+            line: row.line,
+            name: row.name.clone(),
+            error,
+        };
+
+        let name = row
+            .name
+            .as_deref()
+            .ok_or_else(|| fail(PsoidError::EmptyName))?;
+        let version = match non_empty(&row.version) {
+            Some(version) => version.parse().map_err(fail)?,
+            None => default_version,
+        };
+        let class = non_empty(&row.class)
+            .map(str::parse::<CharacterClass>)
+            .transpose()
+            .map_err(fail)?;
+
+        self.name_report_with(name, version, algorithms.get(version), difficulty, class)
+            .map_err(fail)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn malformed(line: usize, message: impl Into<String>) -> RosterError {
+    RosterError {
+        line,
+        name: None,
+        error: PsoidError::MalformedRow(message.into()),
+    }
+}
+
+fn csv_rows(input: &str) -> Result<Vec<Result<Row, RosterError>>, PsoidError> {
+    let mut records = csv_records(input).into_iter();
+
+    let Some((header_line, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header = header.map_err(|message| {
+        PsoidError::MalformedRow(format!("line {}: {}", header_line, message))
+    })?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+
+    let name_column = column("name").ok_or(PsoidError::MissingColumn("name"))?;
+    let version_column = column("version");
+    let class_column = column("class");
+
+    Ok(records
+        .map(|(line, fields)| {
+            let fields = fields.map_err(|message| malformed(line, message))?;
+            let get = |column: Option<usize>| column.and_then(|index| fields.get(index).cloned());
+
+            Ok(Row {
+                line,
+                name: get(Some(name_column)).filter(|name| !name.is_empty()),
+                version: get(version_column),
+                class: get(class_column),
+            })
+        })
+        .collect())
+}
+
+/// Split CSV input into records, each with the line it starts on
+///
+/// Fields are quoted as in RFC 4180, and a quoted field may span lines.
+/// Blank lines between records are skipped.
+pub(crate) fn csv_records(input: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut lines = input.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut text = line.to_string();
+        let (mut fields, mut quoted) = split_record(&text);
+        while quoted {
+            let Some((_, next)) = lines.next() else {
+                break;
+            };
+            text.push('\n');
+            text.push_str(next);
+            (fields, quoted) = split_record(&text);
+        }
+
+        let record = if quoted {
+            Err("unterminated quoted field".to_string())
+        } else {
+            Ok(fields)
+        };
+        records.push((index + 1, record));
+    }
+
+    records
+}
+
+/// Split a CSV record into fields, and say whether a quoted field is still open
+fn split_record(text: &str) -> (Vec<String>, bool) {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(ch) = chars.next() {
+        match (quoted, ch) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, ch) => field.push(ch),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => fields.push(std::mem::take(&mut field)),
+            (false, ch) => field.push(ch),
+        }
+    }
+    fields.push(field);
+
+    (fields, quoted)
+}
+
+fn json_rows(input: &str) -> Vec<Result<Row, RosterError>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line, text)| {
+            let object = JsonObject::parse(text).map_err(|message| malformed(line, message))?;
+            let get = |key: &str| {
+                object
+                    .0
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                    .and_then(|(_, value)| value.clone())
+            };
+
+            Ok(Row {
+                line,
+                name: get("name").filter(|name| !name.is_empty()),
+                version: get("version"),
+                class: get("class"),
+            })
+        })
+        .collect()
+}
+
+/// A flat JSON object whose values are strings, numbers, booleans or null
+///
+/// Numbers and booleans are kept as their text; null becomes `None`.
+struct JsonObject(Vec<(String, Option<String>)>);
+
+impl JsonObject {
+    /// Parse one line with a small parser for flat objects
+    ///
+    /// The same parser is used with and without the `json` feature, so a
+    /// roster is accepted or rejected the same way in every build.
+    fn parse(text: &str) -> Result<JsonObject, String> {
+        let mut parser = JsonParser {
+            chars: text.chars().peekable(),
+        };
+        let mut fields = Vec::new();
+
+        parser.expect('{')?;
+        if !parser.consume('}') {
+            loop {
+                let key = parser.string()?;
+                parser.expect(':')?;
+                fields.push((key, parser.value()?));
+                if parser.consume('}') {
+                    break;
+                }
+                parser.expect(',')?;
+            }
+        }
+
+        parser.skip_whitespace();
+        if parser.chars.next().is_some() {
+            return Err("unexpected text after JSON object".to_string());
+        }
+
+        Ok(JsonObject(fields))
+    }
+}
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if_eq(&expected).is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.consume(expected) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' in JSON object", expected))
+        }
+    }
+
+    fn value(&mut self) -> Result<Option<String>, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') => self.string().map(Some),
+            Some('{') | Some('[') => Err("nested JSON values are not supported".to_string()),
+            Some(_) => {
+                let mut literal = String::new();
+                while let Some(ch) = self
+                    .chars
+                    .next_if(|ch| !matches!(ch, ',' | '}') && !ch.is_whitespace())
+                {
+                    literal.push(ch);
+                }
+                match literal.as_str() {
+                    "null" => Ok(None),
+                    "true" | "false" => Ok(Some(literal)),
+                    _ if is_json_number(&literal) => Ok(Some(literal)),
+                    _ => Err(format!("invalid JSON value: {}", literal)),
+                }
+            }
+            None => Err("unexpected end of JSON object".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('/') => value.push('/'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('u') => value.push(self.unicode_escape()?),
+                    _ => return Err("invalid escape in JSON string".to_string()),
+                },
+                Some(ch) if ch < ' ' => return Err("control character in JSON string".to_string()),
+                Some(ch) => value.push(ch),
+                None => return Err("unterminated JSON string".to_string()),
+            }
+        }
+    }
+
+    /// Decode the `XXXX` of a `\uXXXX` escape, joining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                return Err("unpaired surrogate in JSON string".to_string());
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err("invalid surrogate pair in JSON string".to_string());
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| "invalid \\u escape in JSON string".to_string())
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = self.chars.by_ref().take(4).collect();
+        if digits.len() != 4 {
+            return Err("invalid \\u escape in JSON string".to_string());
+        }
+        u32::from_str_radix(&digits, 16)
+            .map_err(|_| "invalid \\u escape in JSON string".to_string())
+    }
+}
+
+/// Whether text is a JSON number: an optional minus, an integer part without
+/// leading zeros, then an optional fraction and exponent
+fn is_json_number(text: &str) -> bool {
+    let digits = |text: &str| {
+        text.len()
+            - text
+                .trim_start_matches(|ch: char| ch.is_ascii_digit())
+                .len()
+    };
+
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let integer = digits(rest);
+    if integer == 0 || (integer > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[integer..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = digits(fraction);
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let len = digits(exponent);
+        if len == 0 {
+            return false;
+        }
+        rest = &exponent[len..];
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guild;
+
+    #[test]
+    fn test_csv_roster() {
+        let csv = "Class,Name,Version,Notes\n\
+                   RAmar,PSO Player,bb,main\n\
+                   ,\"Big, Guns\",v2,\n\
+                   \n\
+                   ,Bob,,alt\n";
+        let roster = process_roster(csv, RosterFormat::Csv, GameVersion::BlueBurst).unwrap();
+
+        assert!(roster.errors.is_empty());
+        let names: Vec<&str> = roster.reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PSO Player", "Big, Guns", "Bob"]);
+        assert_eq!(roster.reports[0].guild.name, "Bluefull");
+        assert_eq!(roster.reports[0].class, Some(CharacterClass::RAmar));
+        assert_eq!(roster.reports[1].version, GameVersion::V2);
+        assert_eq!(roster.reports[2].version, GameVersion::BlueBurst);
+        assert_eq!(roster.reports[2].class, None);
+    }
+
+    #[test]
+    fn test_roster_keeps_going_past_bad_rows() {
+        let csv = "name,version,class\n\
+                   Bob,v1,\n\
+                   café,v1,\n\
+                   Tank,v9,\n\
+                   Hunter,bb,HUnewm\n\
+                   ,bb,\n\
+                   Corey,bb,HUmar\n\
+                   \"Oops,bb,\n";
+        let roster = process_roster(csv, RosterFormat::Csv, GameVersion::V1).unwrap();
+
+        let names: Vec<&str> = roster.reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Bob", "Corey"]);
+        assert_eq!(roster.reports[1].guild.id, Guild::Whitill.id());
+
+        let errors: Vec<(usize, PsoidError)> = roster
+            .errors
+            .iter()
+            .map(|e| (e.line, e.error.clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (3, PsoidError::NonAscii { index: 3, ch: 'é' }),
+                (4, PsoidError::UnknownVersion("v9".to_string())),
+                (5, PsoidError::UnknownClass("HUnewm".to_string())),
+                (6, PsoidError::EmptyName),
+                (
+                    8,
+                    PsoidError::MalformedRow("unterminated quoted field".to_string())
+                ),
+            ]
+        );
+        assert_eq!(roster.errors[0].name.as_deref(), Some("café"));
+    }
+
+    #[test]
+    fn test_roster_with_set() {
+        let csv = "name,version\nBob,v1\nBob,v2\n";
+        let roster = DropTableSet::default()
+            .process_roster(
+                csv,
+                RosterFormat::Csv,
+                GameVersion::V1,
+                Difficulty::Ultimate,
+            )
+            .unwrap();
+
+        assert_eq!(roster.reports.len(), 1);
+        assert_eq!(roster.reports[0].version, GameVersion::V2);
+        // The default set only has the published table, which isn't tied to
+        // a difficulty
+        assert_eq!(roster.reports[0].guild.difficulty, None);
+        assert_eq!(roster.errors[0].line, 2);
+        assert_eq!(
+            roster.errors[0].error,
+            PsoidError::UnavailableDifficulty {
+                version: GameVersion::V1,
+                difficulty: Difficulty::Ultimate
+            }
+        );
+    }
+
+    #[test]
+    fn test_csv_quoted_field_spans_lines() {
+        let csv = "name,version,notes\n\
+                   Bob,v1,\"first line\n\
+                   \n\
+                   third line\"\n\
+                   Corey,v1,\n";
+        let roster = process_roster(csv, RosterFormat::Csv, GameVersion::V1).unwrap();
+
+        let names: Vec<&str> = roster.reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Bob", "Corey"]);
+
+        let records = csv_records(csv);
+        assert_eq!(records[1].0, 2);
+        assert_eq!(
+            records[1].1.as_ref().unwrap()[2],
+            "first line\n\nthird line"
+        );
+        assert_eq!(records[2].0, 5);
+    }
+
+    #[test]
+    fn test_csv_missing_name_column() {
+        let result = process_roster(
+            "character,version\nBob,v1\n",
+            RosterFormat::Csv,
+            GameVersion::V1,
+        );
+        assert_eq!(result.unwrap_err(), PsoidError::MissingColumn("name"));
+
+        let roster = process_roster("", RosterFormat::Csv, GameVersion::V1).unwrap();
+        assert_eq!(roster, Roster::default());
+    }
+
+    #[test]
+    fn test_json_lines_roster() {
+        let jsonl = "{\"name\": \"PSO Player\", \"version\": \"bb\", \"class\": \"RAmar\"}\n\
+                     {\"name\":\"Bob\",\"class\":null,\"level\":200}\n\
+                     \n\
+                     {\"name\":\"Tab\\tName\",\"version\":\"bb\"}\n\
+                     {\"name\":\"Big \\\"Guns\\\"\",\"version\":\"v2\"}\n\
+                     {\"name\":\"\\u0042ob\"}\n\
+                     not json\n";
+        let roster = process_roster(jsonl, RosterFormat::JsonLines, GameVersion::V1).unwrap();
+
+        let names: Vec<&str> = roster.reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PSO Player", "Bob", "Big \"Guns\"", "Bob"]);
+        assert_eq!(roster.reports[0].guild.name, "Bluefull");
+        assert_eq!(roster.reports[1].guild.name, "Pinkal");
+
+        let lines: Vec<usize> = roster.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![4, 7]);
+        assert_eq!(
+            roster.errors[0].error,
+            PsoidError::UnsupportedCharacter { index: 3, ch: '\t' }
+        );
+        assert!(matches!(
+            roster.errors[1].error,
+            PsoidError::MalformedRow(_)
+        ));
+    }
+
+    // Runs in every build, so the rows accepted and the messages given don't
+    // depend on the `json` feature
+    #[test]
+    fn test_json_syntax() {
+        let jsonl = "{\"name\":\"Bob\",\"level\":-1.5e3,\"main\":true}\n\
+                     {\"name\" \"Bob\"}\n\
+                     {\"name\":\"Bob\",\"level\":012}\n\
+                     {\"name\":\"Bob\",\"level\":NaN}\n\
+                     {\"name\":\"Bob\",\"tags\":[\"a\"]}\n\
+                     {\"name\":\"Bo\u{1}b\"}\n\
+                     {\"name\":\"Bob\"} x\n\
+                     {\"name\":\"Bob\n";
+        let roster = process_roster(jsonl, RosterFormat::JsonLines, GameVersion::V1).unwrap();
+
+        assert_eq!(roster.reports.len(), 1);
+        let errors: Vec<(usize, PsoidError)> = roster
+            .errors
+            .into_iter()
+            .map(|error| (error.line, error.error))
+            .collect();
+        let malformed = |message: &str| PsoidError::MalformedRow(message.to_string());
+        assert_eq!(
+            errors,
+            vec![
+                (2, malformed("expected ':' in JSON object")),
+                (3, malformed("invalid JSON value: 012")),
+                (4, malformed("invalid JSON value: NaN")),
+                (5, malformed("nested JSON values are not supported")),
+                (6, malformed("control character in JSON string")),
+                (7, malformed("unexpected text after JSON object")),
+                (8, malformed("unterminated JSON string")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_invalid_surrogates() {
+        let jsonl = "{\"name\":\"\\uD800\\u0041\"}\n\
+                     {\"name\":\"\\uDC00\"}\n\
+                     {\"name\":\"A\\uD83D\\uDE00\"}\n";
+        let roster = process_roster(jsonl, RosterFormat::JsonLines, GameVersion::V1).unwrap();
+
+        let lines: Vec<usize> = roster.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+        assert!(matches!(
+            roster.errors[0].error,
+            PsoidError::MalformedRow(_)
+        ));
+        assert!(matches!(
+            roster.errors[1].error,
+            PsoidError::MalformedRow(_)
+        ));
+        // A valid pair decodes, then fails as a name
+        assert_eq!(
+            roster.errors[2].error,
+            PsoidError::NonAscii {
+                index: 1,
+                ch: '\u{1F600}'
+            }
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Reverse search: find names that land on a target Section ID
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{calculate, find_names, GameVersion, Guild, SearchOptions};
+//!
+//! let options = SearchOptions {
+//!     prefix: "Rico".to_string(),
+//!     ..SearchOptions::default()
+//! };
+//!
+//! for name in find_names(Guild::Redria, GameVersion::V1, None, &options)
+//!     .unwrap()
+//!     .take(5)
+//! {
+//!     assert!(name.starts_with("Rico"));
+//!     assert_eq!(calculate(&name, GameVersion::V1, None).unwrap(), Guild::Redria);
+//! }
+//! ```
+
+use crate::{CharacterClass, GameVersion, Guild, PsoidError, SectionIdAlgorithm, MAX_NAME_LEN};
+
+/// Constraints for the names produced by [`find_names`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Every name starts with this text
+    pub prefix: String,
+    /// Characters that may follow the prefix, tried in this order
+    pub charset: Vec<char>,
+    /// Shortest name length to produce (including the prefix)
+    pub min_len: usize,
+    /// Longest name length to produce (including the prefix, capped at 12)
+    pub max_len: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            prefix: String::new(),
+            charset: ('A'..='Z').chain('a'..='z').chain('0'..='9').collect(),
+            min_len: 1,
+            max_len: MAX_NAME_LEN,
+        }
+    }
+}
+
+/// Lazy iterator over names that calculate to a target guild
+///
+/// Names are produced shortest first, and in `charset` order within a length.
+#[derive(Debug, Clone)]
+pub struct NameSearch {
+    target: u32,
+    prefix: String,
+    prefix_sum: u32,
+    prefix_width: usize,
+    /// Each character with its [`char_len`](SectionIdAlgorithm::char_len)
+    charset: Vec<(char, usize)>,
+    /// Each charset character's value modulo 10 in each position after the
+    /// prefix, or `None` where the algorithm rejects it
+    values: Vec<Vec<Option<u32>>>,
+    len: usize,
+    max_len: usize,
+    counters: Option<Vec<usize>>,
+}
+
+/// Find names that calculate to `target` for the given version and class
+///
+/// The prefix and every character in the charset must be valid for the
+/// version, otherwise the same error `calculate` would return is reported.
+/// Duplicate characters in the charset are ignored.
+pub fn find_names(
+    target: Guild,
+    version: GameVersion,
+    class: Option<CharacterClass>,
+    options: &SearchOptions,
+) -> Result<NameSearch, PsoidError> {
+    find_names_with(target, version.algorithm(), class, options)
+}
+
+/// Find names that calculate to `target` with the given algorithm and class
+///
+/// Lengths in `options` count characters; names the algorithm measures as
+/// longer than [`MAX_NAME_LEN`] (six full-width kana with
+/// [`ShiftJisAlgorithm`](crate::ShiftJisAlgorithm), for example) are skipped.
+pub fn find_names_with(
+    target: Guild,
+    algorithm: &dyn SectionIdAlgorithm,
+    class: Option<CharacterClass>,
+    options: &SearchOptions,
+) -> Result<NameSearch, PsoidError> {
+    let prefix_len = options.prefix.chars().count();
+    let prefix_width = options
+        .prefix
+        .chars()
+        .map(|ch| algorithm.char_len(ch))
+        .sum();
+    if prefix_width > MAX_NAME_LEN {
+        return Err(PsoidError::NameTooLong {
+            len: prefix_width,
+            max: MAX_NAME_LEN,
+        });
+    }
+
+    // Only the last digit of the sum matters, so every value is kept modulo
+    // 10 and the sums can't overflow.
+    let mut prefix_sum = algorithm.class_offset(class) % 10;
+    for (index, ch) in options.prefix.chars().enumerate() {
+        prefix_sum = (prefix_sum + algorithm.char_value(index, ch)?) % 10;
+    }
+
+    let mut charset: Vec<(char, usize)> = Vec::with_capacity(options.charset.len());
+    for &ch in &options.charset {
+        if charset.iter().any(|&(seen, _)| seen == ch) {
+            continue;
+        }
+        // Check each character where it would first be placed, so an unusable
+        // one is reported like `calculate` would
+        algorithm.char_value(prefix_len, ch)?;
+        charset.push((ch, algorithm.char_len(ch)));
+    }
+
+    let len = options.min_len.max(prefix_len).max(1);
+    let max_len = options.max_len.min(MAX_NAME_LEN);
+    let counters = (len <= max_len).then(|| vec![0; len - prefix_len]);
+
+    // Values can depend on the position, so each slot gets its own
+    let values = (prefix_len..max_len)
+        .map(|index| {
+            charset
+                .iter()
+                .map(|&(ch, _)| algorithm.char_value(index, ch).ok().map(|value| value % 10))
+                .collect()
+        })
+        .collect();
+
+    Ok(NameSearch {
+        target: target.id(),
+        prefix: options.prefix.clone(),
+        prefix_sum,
+        prefix_width,
+        charset,
+        values,
+        len,
+        max_len,
+        counters,
+    })
+}
+
+impl NameSearch {
+    /// Move the counters to the next candidate, growing the name when every
+    /// combination of the current length has been tried
+    fn advance(&mut self) {
+        let Some(counters) = self.counters.as_mut() else {
+            return;
+        };
+
+        for counter in counters.iter_mut().rev() {
+            *counter += 1;
+            if *counter < self.charset.len() {
+                return;
+            }
+            *counter = 0;
+        }
+
+        self.len += 1;
+        self.counters = if self.len <= self.max_len && !self.charset.is_empty() {
+            Some(vec![0; self.len - self.prefix.chars().count()])
+        } else {
+            None
+        };
+    }
+}
+
+impl Iterator for NameSearch {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let counters = self.counters.as_ref()?;
+
+            // A suffix can't be built from an empty charset, so only the
+            // bare prefix is a candidate.
+            if self.charset.is_empty() && !counters.is_empty() {
+                self.counters = None;
+                return None;
+            }
+
+            let sum = counters
+                .iter()
+                .enumerate()
+                .map(|(slot, &index)| self.values[slot][index])
+                .sum::<Option<u32>>()
+                .map(|sum| self.prefix_sum + sum);
+
+            let width = self.prefix_width
+                + counters
+                    .iter()
+                    .map(|&index| self.charset[index].1)
+                    .sum::<usize>();
+
+            let matches = sum.is_some_and(|sum| sum % 10 == self.target);
+            let name = if matches && width <= MAX_NAME_LEN {
+                let mut name = self.prefix.clone();
+                name.extend(counters.iter().map(|&index| self.charset[index].0));
+                Some(name)
+            } else {
+                None
+            };
+
+            self.advance();
+
+            if name.is_some() {
+                return name;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate;
+
+    #[test]
+    fn test_find_names_match_target() {
+        let options = SearchOptions::default();
+        for version in [GameVersion::V1, GameVersion::BlueBurst] {
+            let names: Vec<String> = find_names(
+                Guild::Redria,
+                version,
+                Some(CharacterClass::HUnewearl),
+                &options,
+            )
+            .unwrap()
+            .take(50)
+            .collect();
+
+            assert_eq!(names.len(), 50);
+            for name in names {
+                let guild = calculate(&name, version, Some(CharacterClass::HUnewearl)).unwrap();
+                assert_eq!(guild, Guild::Redria);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_names_order() {
+        let options = SearchOptions {
+            charset: vec!['a', 'b', 'c'],
+            min_len: 1,
+            max_len: 3,
+            ..SearchOptions::default()
+        };
+        let names: Vec<String> = find_names(Guild::Pinkal, GameVersion::V1, None, &options)
+            .unwrap()
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["ab", "ba", "acc", "bbc", "bcb", "cac", "cbb", "cca"]
+        );
+    }
+
+    #[test]
+    fn test_find_names_prefix_and_length() {
+        let options = SearchOptions {
+            prefix: "Rico".to_string(),
+            min_len: 6,
+            max_len: 6,
+            ..SearchOptions::default()
+        };
+        let names: Vec<String> = find_names(Guild::Oran, GameVersion::V2, None, &options)
+            .unwrap()
+            .collect();
+
+        assert!(!names.is_empty());
+        for name in names {
+            assert!(name.starts_with("Rico"));
+            assert_eq!(name.len(), 6);
+            assert_eq!(
+                calculate(&name, GameVersion::V2, None).unwrap(),
+                Guild::Oran
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_names_exhaustive_count() {
+        // Every two-digit name, one in ten lands on each guild
+        let options = SearchOptions {
+            charset: ('0'..='9').collect(),
+            min_len: 2,
+            max_len: 2,
+            ..SearchOptions::default()
+        };
+        let count = find_names(Guild::Skyly, GameVersion::V1, None, &options)
+            .unwrap()
+            .count();
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_find_names_prefix_only() {
+        let options = SearchOptions {
+            prefix: "Bob".to_string(),
+            charset: Vec::new(),
+            ..SearchOptions::default()
+        };
+        let names: Vec<String> = find_names(Guild::Pinkal, GameVersion::V1, None, &options)
+            .unwrap()
+            .collect();
+        assert_eq!(names, vec!["Bob".to_string()]);
+
+        let names: Vec<String> = find_names(Guild::Oran, GameVersion::V1, None, &options)
+            .unwrap()
+            .collect();
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn test_find_names_invalid_input() {
+        let options = SearchOptions {
+            prefix: "café".to_string(),
+            ..SearchOptions::default()
+        };
+        let result = find_names(Guild::Pinkal, GameVersion::V1, None, &options);
+        assert_eq!(
+            result.unwrap_err(),
+            PsoidError::NonAscii { index: 3, ch: 'é' }
+        );
+
+        let options = SearchOptions {
+            charset: vec!['a', '\t'],
+            ..SearchOptions::default()
+        };
+        let result = find_names(Guild::Pinkal, GameVersion::BlueBurst, None, &options);
+        assert_eq!(
+            result.unwrap_err(),
+            PsoidError::UnsupportedCharacter { index: 0, ch: '\t' }
+        );
+    }
+}
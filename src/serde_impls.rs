@@ -0,0 +1,233 @@
+//! Serde support, enabled with the `serde` cargo feature
+//!
+//! Guilds, classes, versions and difficulties serialize as their canonical
+//! names (`"Redria"`, `"HUnewearl"`, `"BlueBurst"`, `"Very Hard"`). Deserializing uses the same
+//! parsing as `FromStr`, so `"redria"`, `"HUNEWEARL"` and `"blue burst"` are
+//! accepted too, and guilds also accept their numeric ID.
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{CharacterClass, Guild};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Character {
+//!     class: CharacterClass,
+//!     guild: Guild,
+//!     #[serde(with = "psoid::guild_id")]
+//!     guild_id: Guild,
+//! }
+//!
+//! let json = r#"{"class":"humar","guild":"REDRIA","guild_id":6}"#;
+//! let character: Character = serde_json::from_str(json).unwrap();
+//! assert_eq!(character.class, CharacterClass::HUmar);
+//! assert_eq!(character.guild, Guild::Redria);
+//! assert_eq!(character.guild_id, Guild::Redria);
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&character).unwrap(),
+//!     r#"{"class":"HUmar","guild":"Redria","guild_id":6}"#
+//! );
+//! ```
+
+use crate::{CharacterClass, Difficulty, GameVersion, Guild};
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+
+impl Serialize for Guild {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Guild {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(GuildVisitor)
+    }
+}
+
+struct GuildVisitor;
+
+impl Visitor<'_> for GuildVisitor {
+    type Value = Guild;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a guild name or an ID from 0 to 9")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Guild, E> {
+        value
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Guild, E> {
+        u32::try_from(value)
+            .ok()
+            .and_then(|id| Guild::try_from(id).ok())
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Guild, E> {
+        u64::try_from(value)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            .and_then(|value| self.visit_u64(value))
+    }
+}
+
+impl Serialize for CharacterClass {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for CharacterClass {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for GameVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for GameVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Difficulty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Serialize a [`Guild`] as its numeric ID (0-9) instead of its name
+///
+/// Use with `#[serde(with = "psoid::guild_id")]`. Deserializing accepts both
+/// IDs and names.
+pub mod guild_id {
+    use crate::Guild;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(guild: &Guild, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(guild.id())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Guild, D::Error> {
+        Guild::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_names() {
+        for guild in Guild::ALL {
+            let json = serde_json::to_string(&guild).unwrap();
+            assert_eq!(json, format!("\"{}\"", guild.name()));
+            assert_eq!(serde_json::from_str::<Guild>(&json).unwrap(), guild);
+        }
+        for class in CharacterClass::ALL {
+            let json = serde_json::to_string(&class).unwrap();
+            assert_eq!(json, format!("\"{}\"", class.name()));
+            assert_eq!(
+                serde_json::from_str::<CharacterClass>(&json).unwrap(),
+                class
+            );
+        }
+        for version in GameVersion::ALL {
+            let json = serde_json::to_string(&version).unwrap();
+            assert_eq!(serde_json::from_str::<GameVersion>(&json).unwrap(), version);
+        }
+        for difficulty in Difficulty::ALL {
+            let json = serde_json::to_string(&difficulty).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Difficulty>(&json).unwrap(),
+                difficulty
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_case_variants() {
+        for text in ["\"redria\"", "\"REDRIA\"", "\"Redria\"", "6"] {
+            assert_eq!(serde_json::from_str::<Guild>(text).unwrap(), Guild::Redria);
+        }
+        for text in ["\"hunewearl\"", "\"HUNEWEARL\"", "\"HUnewearl\""] {
+            assert_eq!(
+                serde_json::from_str::<CharacterClass>(text).unwrap(),
+                CharacterClass::HUnewearl
+            );
+        }
+        for text in ["\"blueburst\"", "\"Blue Burst\"", "\"bb\""] {
+            assert_eq!(
+                serde_json::from_str::<GameVersion>(text).unwrap(),
+                GameVersion::BlueBurst
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        assert!(serde_json::from_str::<Guild>("\"Purple\"").is_err());
+        assert!(serde_json::from_str::<Guild>("10").is_err());
+        assert!(serde_json::from_str::<Guild>("-1").is_err());
+        assert!(serde_json::from_str::<CharacterClass>("\"HUnewm\"").is_err());
+    }
+
+    #[test]
+    fn test_guild_id() {
+        #[derive(Serialize, Deserialize)]
+        struct Row {
+            #[serde(with = "guild_id")]
+            guild: Guild,
+        }
+
+        let json = serde_json::to_string(&Row {
+            guild: Guild::Whitill,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"guild":9}"#);
+        assert_eq!(
+            serde_json::from_str::<Row>(&json).unwrap().guild,
+            Guild::Whitill
+        );
+    }
+
+    #[test]
+    fn test_drop_rates_and_reports() {
+        let rates = Guild::Oran.drop_rates();
+        let json = serde_json::to_string(&rates).unwrap();
+        assert_eq!(
+            serde_json::from_str::<crate::DropRates>(&json).unwrap(),
+            rates
+        );
+
+        // The serde output and the built-in JSON format must agree
+        for class in [None, Some(CharacterClass::FOmarl)] {
+            let report = crate::NameReport::new("Bob", GameVersion::BlueBurst, class).unwrap();
+            assert_eq!(
+                serde_json::to_string(&report).unwrap(),
+                report.render(crate::OutputFormat::Json)
+            );
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Minimal-edit suggestions to move an existing name to a different Section ID
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{calculate, suggest_edits, GameVersion, Guild};
+//!
+//! let suggestions = suggest_edits("Corey", GameVersion::V1, None, Guild::Redria).unwrap();
+//! let best = &suggestions[0];
+//!
+//! assert_eq!(calculate(&best.name, GameVersion::V1, None).unwrap(), Guild::Redria);
+//! ```
+
+use crate::{
+    calculate_with, CharacterClass, GameVersion, Guild, PsoidError, SectionIdAlgorithm,
+    MAX_NAME_LEN,
+};
+
+/// A single change made to a name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// The letter at `index` had its case flipped
+    CaseFlip { index: usize, from: char, to: char },
+    /// The digit at `index` was replaced with another digit
+    DigitSwap { index: usize, from: char, to: char },
+    /// The character at `index` was replaced with any other character
+    Substitute { index: usize, from: char, to: char },
+    /// A character was added to the end of the name
+    Append { ch: char },
+}
+
+/// A name that reaches the target guild with a single edit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The edited name
+    pub name: String,
+    /// The change that was made
+    pub edit: Edit,
+    /// How different the name looks from the original (lower is closer)
+    pub cost: u32,
+}
+
+/// Pairs of characters that read almost the same in a name
+const LOOK_ALIKES: [(char, char); 9] = [
+    ('o', '0'),
+    ('O', '0'),
+    ('l', '1'),
+    ('I', '1'),
+    ('i', '1'),
+    ('e', '3'),
+    ('a', '4'),
+    ('s', '5'),
+    ('t', '7'),
+];
+
+impl Edit {
+    /// How noticeable the edit is
+    fn cost(&self) -> u32 {
+        match *self {
+            Edit::CaseFlip { .. } => 1,
+            Edit::DigitSwap { .. } => 3,
+            Edit::Substitute { from, to, .. } => {
+                let look_alike = LOOK_ALIKES
+                    .iter()
+                    .any(|&(a, b)| (a, b) == (from, to) || (b, a) == (from, to));
+                if look_alike {
+                    2
+                } else {
+                    5
+                }
+            }
+            Edit::Append { ch } => {
+                if ch.is_ascii_punctuation() || ch.is_ascii_digit() {
+                    4
+                } else {
+                    6
+                }
+            }
+        }
+    }
+
+    /// Position of the edited character (appends come after the name)
+    fn index(&self, name_len: usize) -> usize {
+        match *self {
+            Edit::CaseFlip { index, .. }
+            | Edit::DigitSwap { index, .. }
+            | Edit::Substitute { index, .. } => index,
+            Edit::Append { .. } => name_len,
+        }
+    }
+}
+
+/// Suggest single-character edits that move `name` to the `target` guild
+///
+/// Suggestions are ranked so the ones that look closest to the original name
+/// come first: case flips, then look-alike substitutions (`o` and `0`), digit
+/// swaps, appended digits or punctuation, any other substitution, and finally
+/// appended letters.
+/// Edits near the end of the name are preferred over edits near the start.
+///
+/// Returns an empty list if the name already belongs to the target guild.
+pub fn suggest_edits(
+    name: &str,
+    version: GameVersion,
+    class: Option<CharacterClass>,
+    target: Guild,
+) -> Result<Vec<Suggestion>, PsoidError> {
+    suggest_edits_with(name, version.algorithm(), class, target)
+}
+
+/// Suggest single-character edits with the given algorithm
+pub fn suggest_edits_with(
+    name: &str,
+    algorithm: &dyn SectionIdAlgorithm,
+    class: Option<CharacterClass>,
+    target: Guild,
+) -> Result<Vec<Suggestion>, PsoidError> {
+    if calculate_with(name, algorithm, class)? == target {
+        return Ok(Vec::new());
+    }
+
+    // Only the last digit of the sum matters, so values are kept modulo 10
+    let chars: Vec<char> = name.chars().collect();
+    let mut values = Vec::with_capacity(chars.len());
+    for (index, &ch) in chars.iter().enumerate() {
+        values.push(algorithm.char_value(index, ch)? % 10);
+    }
+    let sum = values.iter().sum::<u32>() + algorithm.class_offset(class) % 10;
+
+    // Values and which characters are accepted can depend on the position,
+    // so every printable character is checked where it would go
+    let candidates: Vec<char> = (' '..='~').collect();
+    let mut suggestions = Vec::new();
+
+    for (index, &from) in chars.iter().enumerate() {
+        let at_edge = index == 0 || index == chars.len() - 1;
+        let rest = sum - values[index];
+
+        for &to in &candidates {
+            if to == from || (to == ' ' && at_edge) {
+                continue;
+            }
+            let Ok(value) = algorithm.char_value(index, to) else {
+                continue;
+            };
+            if (rest + value % 10) % 10 != target.id() {
+                continue;
+            }
+
+            let edit = if from.is_ascii_alphabetic() && to == flip_case(from) {
+                Edit::CaseFlip { index, from, to }
+            } else if from.is_ascii_digit() && to.is_ascii_digit() {
+                Edit::DigitSwap { index, from, to }
+            } else {
+                Edit::Substitute { index, from, to }
+            };
+
+            let mut edited = chars.clone();
+            edited[index] = to;
+            suggestions.push(Suggestion {
+                name: edited.into_iter().collect(),
+                edit,
+                cost: edit.cost(),
+            });
+        }
+    }
+
+    let width: usize = chars.iter().map(|&ch| algorithm.char_len(ch)).sum();
+    for &ch in &candidates {
+        if ch == ' ' || width + algorithm.char_len(ch) > MAX_NAME_LEN {
+            continue;
+        }
+        let Ok(value) = algorithm.char_value(chars.len(), ch) else {
+            continue;
+        };
+        if (sum + value % 10) % 10 != target.id() {
+            continue;
+        }
+
+        let edit = Edit::Append { ch };
+        suggestions.push(Suggestion {
+            name: format!("{}{}", name, ch),
+            edit,
+            cost: edit.cost(),
+        });
+    }
+
+    suggestions.sort_by(|a, b| {
+        a.cost
+            .cmp(&b.cost)
+            .then(b.edit.index(chars.len()).cmp(&a.edit.index(chars.len())))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(suggestions)
+}
+
+fn flip_case(ch: char) -> char {
+    if ch.is_ascii_uppercase() {
+        ch.to_ascii_lowercase()
+    } else {
+        ch.to_ascii_uppercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate;
+
+    #[test]
+    fn test_suggestions_reach_target() {
+        for version in [GameVersion::V1, GameVersion::V2, GameVersion::BlueBurst] {
+            let class = Some(CharacterClass::RAmarl);
+            let suggestions = suggest_edits("Hunter", version, class, Guild::Skyly).unwrap();
+
+            assert!(!suggestions.is_empty());
+            for suggestion in suggestions {
+                let guild = calculate(&suggestion.name, version, class).unwrap();
+                assert_eq!(guild, Guild::Skyly);
+            }
+        }
+    }
+
+    #[test]
+    fn test_suggestions_already_on_target() {
+        let suggestions = suggest_edits("Bob", GameVersion::V1, None, Guild::Pinkal).unwrap();
+        assert!(suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_case_flip_ranked_first() {
+        // 'b' -> 'B' removes 32 from the sum, moving Pinkal (5) to Bluefull (3)
+        let suggestions = suggest_edits("Bob", GameVersion::V1, None, Guild::Bluefull).unwrap();
+        assert_eq!(suggestions[0].name, "BoB");
+        assert_eq!(
+            suggestions[0].edit,
+            Edit::CaseFlip {
+                index: 2,
+                from: 'b',
+                to: 'B'
+            }
+        );
+        assert!(suggestions.windows(2).all(|w| w[0].cost <= w[1].cost));
+    }
+
+    #[test]
+    fn test_digit_swap_and_append() {
+        let suggestions = suggest_edits("Tank1", GameVersion::V1, None, Guild::Redria).unwrap();
+
+        assert!(suggestions.iter().any(|s| s.name == "Tank0"
+            && s.edit
+                == Edit::DigitSwap {
+                    index: 4,
+                    from: '1',
+                    to: '0'
+                }));
+        assert!(suggestions
+            .iter()
+            .any(|s| matches!(s.edit, Edit::Append { .. })));
+    }
+
+    #[test]
+    fn test_no_append_at_max_length() {
+        let suggestions =
+            suggest_edits("ABCDEFGHIJKL", GameVersion::V1, None, Guild::Viridia).unwrap();
+        assert!(!suggestions.is_empty());
+        assert!(suggestions
+            .iter()
+            .all(|s| !matches!(s.edit, Edit::Append { .. })));
+    }
+
+    #[test]
+    fn test_suggestions_invalid_name() {
+        let result = suggest_edits("café", GameVersion::V1, None, Guild::Oran);
+        assert_eq!(
+            result.unwrap_err(),
+            PsoidError::NonAscii { index: 3, ch: 'é' }
+        );
+    }
+}
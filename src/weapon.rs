@@ -0,0 +1,355 @@
+//! Weapon types and typed access to drop rates
+//!
+//! # Examples
+//!
+//! ```
+//! use psoid::{Guild, WeaponType};
+//!
+//! let rates = Guild::Oran.drop_rates();
+//! assert_eq!(rates[WeaponType::Daggers], 13);
+//!
+//! let (weapon, rate) = rates.sorted()[0];
+//! assert_eq!((weapon, rate), (WeaponType::Sabers, 13));
+//!
+//! assert_eq!(Guild::best_for(WeaponType::Slicers), vec![Guild::Whitill]);
+//! ```
+
+use crate::{DropRates, Guild, PsoidError};
+use std::cmp::Reverse;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// The twelve weapon categories in guild drop tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WeaponType {
+    Sabers,
+    Swords,
+    Daggers,
+    Partisans,
+    Slicers,
+    Handguns,
+    Rifles,
+    Machineguns,
+    Shotguns,
+    Canes,
+    Rods,
+    Wands,
+}
+
+impl WeaponType {
+    /// Every weapon type, in drop table order
+    pub const ALL: [WeaponType; 12] = [
+        WeaponType::Sabers,
+        WeaponType::Swords,
+        WeaponType::Daggers,
+        WeaponType::Partisans,
+        WeaponType::Slicers,
+        WeaponType::Handguns,
+        WeaponType::Rifles,
+        WeaponType::Machineguns,
+        WeaponType::Shotguns,
+        WeaponType::Canes,
+        WeaponType::Rods,
+        WeaponType::Wands,
+    ];
+
+    /// Get the name of the weapon type as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponType::Sabers => "Sabers",
+            WeaponType::Swords => "Swords",
+            WeaponType::Daggers => "Daggers",
+            WeaponType::Partisans => "Partisans",
+            WeaponType::Slicers => "Slicers",
+            WeaponType::Handguns => "Handguns",
+            WeaponType::Rifles => "Rifles",
+            WeaponType::Machineguns => "Machineguns",
+            WeaponType::Shotguns => "Shotguns",
+            WeaponType::Canes => "Canes",
+            WeaponType::Rods => "Rods",
+            WeaponType::Wands => "Wands",
+        }
+    }
+}
+
+impl fmt::Display for WeaponType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a weapon type ignoring case, with or without the trailing "s"
+/// ("Sabers", "saber" and "SABER" all work)
+impl FromStr for WeaponType {
+    type Err = PsoidError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        WeaponType::ALL
+            .into_iter()
+            .find(|weapon| {
+                let name = weapon.name();
+                name.eq_ignore_ascii_case(value)
+                    || name[..name.len() - 1].eq_ignore_ascii_case(value)
+            })
+            .ok_or_else(|| PsoidError::UnknownWeapon(value.to_string()))
+    }
+}
+
+/// A guild's most common or rarest weapon drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponDrop {
+    /// One weapon type stands out at the given percentage
+    Weapon { weapon: WeaponType, percent: u32 },
+    /// Every weapon type drops about equally (Yellowboze)
+    AllEqual,
+}
+
+impl WeaponDrop {
+    /// Get the weapon type, or `None` when every weapon drops equally
+    pub fn weapon(&self) -> Option<WeaponType> {
+        match self {
+            WeaponDrop::Weapon { weapon, .. } => Some(*weapon),
+            WeaponDrop::AllEqual => None,
+        }
+    }
+
+    /// Get the drop percentage, or `None` when every weapon drops equally
+    pub fn percent(&self) -> Option<u32> {
+        match self {
+            WeaponDrop::Weapon { percent, .. } => Some(*percent),
+            WeaponDrop::AllEqual => None,
+        }
+    }
+
+    /// Get the weapon name, or "All Equal"
+    pub fn name(&self) -> &'static str {
+        self.weapon().map_or("All Equal", |weapon| weapon.name())
+    }
+
+    /// Build a drop for `weapon` with its rate in `rates`, or `AllEqual` for `None`
+    pub(crate) fn from_rates(weapon: Option<WeaponType>, rates: &DropRates) -> WeaponDrop {
+        match weapon {
+            Some(weapon) => WeaponDrop::Weapon {
+                weapon,
+                percent: rates[weapon],
+            },
+            None => WeaponDrop::AllEqual,
+        }
+    }
+}
+
+impl fmt::Display for WeaponDrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeaponDrop::Weapon { weapon, percent } => write!(f, "{} ({}%)", weapon, percent),
+            WeaponDrop::AllEqual => f.write_str("All Equal"),
+        }
+    }
+}
+
+impl DropRates {
+    /// Get the drop rate for a weapon type
+    pub fn get(&self, weapon: WeaponType) -> u32 {
+        self[weapon]
+    }
+
+    /// Iterate over every weapon type and its rate, in drop table order
+    pub fn iter(&self) -> impl Iterator<Item = (WeaponType, u32)> + '_ {
+        WeaponType::ALL
+            .into_iter()
+            .map(move |weapon| (weapon, self[weapon]))
+    }
+
+    /// Get every weapon type and its rate, highest rate first
+    ///
+    /// Weapons with the same rate stay in drop table order.
+    pub fn sorted(&self) -> Vec<(WeaponType, u32)> {
+        let mut rates: Vec<(WeaponType, u32)> = self.iter().collect();
+        rates.sort_by_key(|&(_, rate)| Reverse(rate));
+        rates
+    }
+}
+
+impl Index<WeaponType> for DropRates {
+    type Output = u32;
+
+    fn index(&self, weapon: WeaponType) -> &u32 {
+        match weapon {
+            WeaponType::Sabers => &self.sabers,
+            WeaponType::Swords => &self.swords,
+            WeaponType::Daggers => &self.daggers,
+            WeaponType::Partisans => &self.partisans,
+            WeaponType::Slicers => &self.slicers,
+            WeaponType::Handguns => &self.handguns,
+            WeaponType::Rifles => &self.rifles,
+            WeaponType::Machineguns => &self.machineguns,
+            WeaponType::Shotguns => &self.shotguns,
+            WeaponType::Canes => &self.canes,
+            WeaponType::Rods => &self.rods,
+            WeaponType::Wands => &self.wands,
+        }
+    }
+}
+
+impl IndexMut<WeaponType> for DropRates {
+    fn index_mut(&mut self, weapon: WeaponType) -> &mut u32 {
+        match weapon {
+            WeaponType::Sabers => &mut self.sabers,
+            WeaponType::Swords => &mut self.swords,
+            WeaponType::Daggers => &mut self.daggers,
+            WeaponType::Partisans => &mut self.partisans,
+            WeaponType::Slicers => &mut self.slicers,
+            WeaponType::Handguns => &mut self.handguns,
+            WeaponType::Rifles => &mut self.rifles,
+            WeaponType::Machineguns => &mut self.machineguns,
+            WeaponType::Shotguns => &mut self.shotguns,
+            WeaponType::Canes => &mut self.canes,
+            WeaponType::Rods => &mut self.rods,
+            WeaponType::Wands => &mut self.wands,
+        }
+    }
+}
+
+impl Guild {
+    /// Get the guilds with the highest drop rate for a weapon type
+    ///
+    /// Several guilds are returned when they tie, in ID order.
+    pub fn best_for(weapon: WeaponType) -> Vec<Guild> {
+        Guild::best_by(weapon, |guild| guild.drop_rates())
+    }
+
+    /// Get the guilds with the lowest drop rate for a weapon type
+    ///
+    /// Several guilds are returned when they tie, in ID order.
+    pub fn worst_for(weapon: WeaponType) -> Vec<Guild> {
+        Guild::worst_by(weapon, |guild| guild.drop_rates())
+    }
+
+    /// [`Guild::best_for`] with each guild's rates from `rates`
+    pub(crate) fn best_by(weapon: WeaponType, rates: impl Fn(Guild) -> DropRates) -> Vec<Guild> {
+        let rates = Guild::ALL.map(|guild| rates(guild)[weapon]);
+        let best = rates.iter().copied().max().unwrap_or_default();
+        guilds_with_rate(&rates, best)
+    }
+
+    /// [`Guild::worst_for`] with each guild's rates from `rates`
+    pub(crate) fn worst_by(weapon: WeaponType, rates: impl Fn(Guild) -> DropRates) -> Vec<Guild> {
+        let rates = Guild::ALL.map(|guild| rates(guild)[weapon]);
+        let worst = rates.iter().copied().min().unwrap_or_default();
+        guilds_with_rate(&rates, worst)
+    }
+
+    /// Get this guild's weapon types sorted by drop rate, highest first
+    pub fn weapons_by_rate(&self) -> Vec<(WeaponType, u32)> {
+        self.drop_rates().sorted()
+    }
+}
+
+/// Get the guilds whose rate (in [`Guild::ALL`] order) is `wanted`
+fn guilds_with_rate(rates: &[u32; 10], wanted: u32) -> Vec<Guild> {
+    Guild::ALL
+        .into_iter()
+        .zip(rates)
+        .filter(|&(_, &rate)| rate == wanted)
+        .map(|(guild, _)| guild)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_matches_fields() {
+        let rates = Guild::Viridia.drop_rates();
+        assert_eq!(rates[WeaponType::Sabers], rates.sabers);
+        assert_eq!(rates[WeaponType::Partisans], 10);
+        assert_eq!(rates.get(WeaponType::Slicers), 1);
+        assert_eq!(rates.iter().count(), 12);
+        assert_eq!(
+            rates.iter().map(|(_, rate)| rate).sum::<u32>(),
+            rates.sabers
+                + rates.swords
+                + rates.daggers
+                + rates.partisans
+                + rates.slicers
+                + rates.handguns
+                + rates.rifles
+                + rates.machineguns
+                + rates.shotguns
+                + rates.canes
+                + rates.rods
+                + rates.wands
+        );
+
+        let mut rates = rates;
+        rates[WeaponType::Wands] = 20;
+        assert_eq!(rates.wands, 20);
+    }
+
+    #[test]
+    fn test_sorted() {
+        let sorted = Guild::Purplenum.weapons_by_rate();
+        let rates: Vec<u32> = sorted.iter().map(|(_, rate)| *rate).collect();
+        assert!(rates.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(
+            &sorted[..4],
+            &[
+                (WeaponType::Sabers, 13),
+                (WeaponType::Handguns, 13),
+                (WeaponType::Machineguns, 13),
+                (WeaponType::Canes, 13),
+            ]
+        );
+        assert_eq!(sorted[11], (WeaponType::Partisans, 3));
+    }
+
+    #[test]
+    fn test_best_and_worst_for() {
+        assert_eq!(Guild::best_for(WeaponType::Sabers), Guild::ALL.to_vec());
+        assert_eq!(
+            Guild::best_for(WeaponType::Partisans),
+            vec![Guild::Bluefull]
+        );
+        assert_eq!(
+            Guild::worst_for(WeaponType::Partisans),
+            vec![Guild::Purplenum]
+        );
+        assert_eq!(Guild::best_for(WeaponType::Daggers), vec![Guild::Oran]);
+        assert_eq!(Guild::worst_for(WeaponType::Swords), vec![Guild::Greennill]);
+    }
+
+    #[test]
+    fn test_common_and_rare_drops_match_rates() {
+        for guild in Guild::ALL {
+            let rates = guild.drop_rates();
+            for drop in [guild.common_drop(), guild.rare_drop()] {
+                match drop {
+                    WeaponDrop::Weapon { weapon, percent } => assert_eq!(rates[weapon], percent),
+                    WeaponDrop::AllEqual => assert_eq!(guild, Guild::Yellowboze),
+                }
+            }
+        }
+        assert_eq!(Guild::Yellowboze.common_drop().percent(), None);
+        assert_eq!(Guild::Yellowboze.rare_drop().to_string(), "All Equal");
+        assert_eq!(Guild::Oran.rare_drop().to_string(), "Rods (1%)");
+    }
+
+    #[test]
+    fn test_parse_weapon_type() {
+        for weapon in WeaponType::ALL {
+            assert_eq!(weapon.name().parse::<WeaponType>().unwrap(), weapon);
+            assert_eq!(
+                weapon.name().to_lowercase().parse::<WeaponType>().unwrap(),
+                weapon
+            );
+        }
+        assert_eq!("saber".parse::<WeaponType>().unwrap(), WeaponType::Sabers);
+        assert_eq!(
+            "Mechgun".parse::<WeaponType>().unwrap_err(),
+            PsoidError::UnknownWeapon("Mechgun".to_string())
+        );
+    }
+}
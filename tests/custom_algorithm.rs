@@ -0,0 +1,109 @@
+//! A custom algorithm goes through the same public API as the built-in ones,
+//! so the two can be compared side by side.
+
+use psoid::{
+    calculate, calculate_with, compare, compare_with, explain_with, find_names_with,
+    suggest_edits_with, Algorithms, GameVersion, Guild, PsoidError, SearchOptions,
+    SectionIdAlgorithm,
+};
+
+/// Counts every character as 1, so the Section ID is the name's length
+struct Length;
+
+impl SectionIdAlgorithm for Length {
+    fn name(&self) -> &str {
+        "length"
+    }
+
+    fn char_value(&self, _index: usize, _ch: char) -> Result<u32, PsoidError> {
+        Ok(1)
+    }
+}
+
+/// Weights each character's byte by its position, so moving a character
+/// changes its value
+struct Positional;
+
+impl SectionIdAlgorithm for Positional {
+    fn name(&self) -> &str {
+        "positional"
+    }
+
+    fn char_value(&self, index: usize, ch: char) -> Result<u32, PsoidError> {
+        if !ch.is_ascii_alphanumeric() {
+            return Err(PsoidError::UnsupportedCharacter { index, ch });
+        }
+        Ok(ch as u32 * (index as u32 + 1))
+    }
+}
+
+#[test]
+fn test_position_dependent_algorithm() {
+    assert_eq!(calculate_with("ab", &Positional, None).unwrap().id(), 3);
+    assert_eq!(calculate_with("ba", &Positional, None).unwrap().id(), 2);
+
+    let options = SearchOptions {
+        prefix: "Rico".to_string(),
+        max_len: 6,
+        ..SearchOptions::default()
+    };
+    for guild in Guild::ALL {
+        let names: Vec<String> = find_names_with(guild, &Positional, None, &options)
+            .unwrap()
+            .collect();
+        assert!(!names.is_empty());
+        for name in names {
+            assert_eq!(
+                calculate_with(&name, &Positional, None).unwrap(),
+                guild,
+                "{}",
+                name
+            );
+        }
+
+        for suggestion in suggest_edits_with("Rico", &Positional, None, guild).unwrap() {
+            assert_eq!(
+                calculate_with(&suggestion.name, &Positional, None).unwrap(),
+                guild,
+                "{}",
+                suggestion.name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_custom_algorithm_next_to_builtin() {
+    assert_eq!(
+        calculate("Bob", GameVersion::V2, None).unwrap(),
+        Guild::Pinkal
+    );
+    assert_eq!(
+        calculate_with("Bob", &Length, None).unwrap(),
+        Guild::Bluefull
+    );
+
+    let explanation = explain_with("Bob", GameVersion::V2, &Length, None).unwrap();
+    assert_eq!(explanation.sum, 3);
+    assert!(explanation.steps.iter().all(|step| step.value == 1));
+
+    let options = SearchOptions::default();
+    let name = find_names_with(Guild::Skyly, &Length, None, &options)
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(name.len(), 2);
+
+    let suggestions = suggest_edits_with("Bob", &Length, None, Guild::Purplenum).unwrap();
+    assert!(suggestions.iter().all(|s| s.name.len() == 4));
+
+    let algorithms = Algorithms::builtin().with(GameVersion::V2, &Length);
+    let custom = compare_with("Bob", &algorithms).unwrap();
+    let builtin = compare("Bob").unwrap();
+    assert_eq!(custom.entries[1].version, GameVersion::V2);
+    assert_eq!(custom.entries[1].result, Ok(Guild::Bluefull));
+    assert!(custom.entries[1].differs);
+    assert_eq!(builtin.entries[1].result, Ok(Guild::Pinkal));
+    assert!(!builtin.entries[1].differs);
+    assert_eq!(custom.entries[0], builtin.entries[0]);
+}